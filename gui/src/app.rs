@@ -0,0 +1,1716 @@
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Arc;
+
+use egui::emath::TSTransform;
+use egui::{Align2, FontId, Frame, Key, Margin, Rounding, Sense, Stroke};
+use egui::{Color32, Modifiers, Pos2, Rect, Shape, Vec2};
+
+use celestial::cosmos_object::CosmosObject;
+use celestial::math::DVec2;
+use celestial::orbit::{dominant_attractor, OrbitalElements};
+use celestial::physics::{compare_with_direct_sum, AccuracyReport, IntegratorKind, SolverKind};
+use celestial::physics::{CollisionEvent, CollisionOutcome, CollisionResponse, Edit, FrameBuffer};
+use celestial::physics::{Prediction, SimulationState, StepMode, Trajectory, KM_PER_VPX};
+use celestial::recorder::{RecordFormat, Recorder};
+use celestial::scenario::{Camera, Scenario, ScenarioError};
+
+use crate::plot::PlotWindow;
+use crate::trails::Trails;
+use crate::undo::{Command, UndoStack};
+use crate::utils::{format_time, format_time_ord, Painter, ToDVec2, ToEgui, SECONDS_PER_DAY};
+
+const BACKGROUND_COLOR: Color32 = Color32::from_gray(27);
+const GRID_COLOR: Color32 = Color32::from_gray(40);
+const COLLISION_COLOR: Color32 = Color32::from_rgb(255, 170, 60);
+const SELECTION_COLOR: Color32 = Color32::from_gray(220);
+const PREVIEW_COLOR: Color32 = Color32::from_gray(150);
+
+pub struct App {
+    pub frames: Arc<FrameBuffer>,
+
+    sim_state: &'static SimulationState,
+
+    moving: Option<Moving>,
+    adding: Option<Adding>,
+    transform: TSTransform,
+    adding_mass: f64,
+    showed_quantity: Option<PhysicalQuantity>,
+    quantity_scale: [f32; 4],
+    cell_size: f32,
+    accuracy_report: Option<AccuracyReport>,
+    /// recent collisions with the time they were received at
+    collisions: VecDeque<(CollisionEvent, f64)>,
+    /// steps taken by one press of the step control
+    steps_per_click: usize,
+    /// target tick rate restored when the max speed mode is turned off
+    tick_rate: f64,
+    /// file the scenario is saved to
+    scenario_path: String,
+    /// simulated seconds between two samples of a trajectory recording
+    record_interval: f64,
+    record_format: RecordFormat,
+    /// file the trajectory recording is saved to
+    record_path: String,
+    /// result of the last save
+    file_status: Option<String>,
+    plot: PlotWindow,
+    /// id of the body shown in the inspector
+    selected: Option<u64>,
+    /// area of the screen not covered by panels
+    viewport: Rect,
+    /// whether the bodies can be dragged, deleted and duplicated
+    edit_mode: bool,
+    /// whether dragged bodies keep the shape of their orbit
+    keep_orbits: bool,
+    dragging: Option<Dragging>,
+    undo: UndoStack,
+    trails: Trails,
+    /// simulated seconds the trajectory of the body being added is predicted for
+    preview_horizon: f64,
+    preview: Option<Preview>,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        ctx.request_repaint();
+
+        self.update_collisions(ctx);
+        self.show_simulation_window(ctx);
+        self.show_diagnostics_window(ctx);
+        self.show_plot_window(ctx);
+        self.show_inspector_panel(ctx);
+
+        self.update_selecting(ctx);
+        self.update_dragging(ctx);
+        self.update_moving(ctx);
+        self.update_editing(ctx);
+        self.update_undo(ctx);
+        self.update_adding(ctx);
+        self.update_zoom(ctx);
+        self.update_showed_quantity(ctx);
+        self.update_pause(ctx);
+
+        self.autoscale_grid();
+
+        egui::CentralPanel::default()
+            .frame(Frame {
+                inner_margin: Margin::ZERO,
+                outer_margin: Margin::ZERO,
+                fill: BACKGROUND_COLOR,
+                ..Default::default()
+            })
+            .show(ctx, |ui| {
+                let (response, ref painter) =
+                    ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+
+                self.viewport = response.rect;
+
+                let painter = Painter {
+                    raw: painter,
+                    transform: self.transform,
+                };
+
+                self.draw_grid(painter, ui.min_size());
+                self.draw_planets(painter);
+                self.draw_collisions(ctx, painter);
+                self.show_info(painter, ui.min_size());
+
+                let Some(mouse_pos) = ctx.input(|state| state.pointer.hover_pos()) else {
+                    return;
+                };
+
+                if let Some(adding) = self.adding {
+                    let new_object = self.adding_object(adding, mouse_pos);
+
+                    self.draw_preview(painter, &new_object);
+
+                    draw_object(painter, &new_object);
+
+                    self.draw_quantity_vec(painter, PhysicalQuantity::Velocity, &new_object);
+
+                    ctx.input(|state| {
+                        for event in &state.events {
+                            if let egui::Event::MouseWheel { delta, .. } = event {
+                                self.adding_mass *= 1.7f64.powf(delta.y as f64);
+                            }
+                        }
+                    });
+                }
+            });
+    }
+}
+
+impl App {
+    pub fn new(
+        frames: Arc<FrameBuffer>,
+        transform: TSTransform,
+        sim_state: &'static SimulationState,
+        scenario_path: String,
+    ) -> Self {
+        Self {
+            frames,
+            transform,
+
+            sim_state,
+
+            moving: None,
+            adding: None,
+            adding_mass: 20.0,
+            showed_quantity: None,
+            quantity_scale: [1.0, 1.0, 1.0, 1.0],
+            cell_size: 20.0,
+            accuracy_report: None,
+            collisions: VecDeque::new(),
+            steps_per_click: 1,
+            tick_rate: sim_state.tick_rate().unwrap_or(240.0),
+            scenario_path,
+            record_interval: 60.0 * 60.0,
+            record_format: RecordFormat::Csv,
+            record_path: format!("trajectory.{}", RecordFormat::Csv.extension()),
+            file_status: None,
+            plot: PlotWindow::new(),
+            selected: None,
+            viewport: Rect::EVERYTHING,
+            edit_mode: false,
+            keep_orbits: true,
+            dragging: None,
+            undo: UndoStack::default(),
+            trails: Trails::new(),
+            preview_horizon: 90.0 * SECONDS_PER_DAY,
+            preview: None,
+        }
+    }
+
+    fn show_simulation_window(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Simulation")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let paused = self.sim_state.paused();
+
+                    if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                        self.sim_state.set_paused(!paused);
+                    }
+
+                    let step = ui.add_enabled(paused, egui::Button::new("Step"));
+
+                    if step.clicked() {
+                        self.sim_state.request_steps(self.steps_per_click);
+                    }
+
+                    ui.add(
+                        egui::DragValue::new(&mut self.steps_per_click)
+                            .range(1..=100_000)
+                            .suffix(" steps"),
+                    );
+                });
+
+                let mut tick_rate = self.sim_state.tick_rate();
+                let mut max_speed = tick_rate.is_none();
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut max_speed, "Max speed");
+
+                    if let Some(tick_rate) = tick_rate.as_mut() {
+                        ui.add(
+                            egui::DragValue::new(tick_rate)
+                                .range(1.0..=10_000.0)
+                                .suffix(" tps"),
+                        );
+                    }
+                });
+
+                let tick_rate = match (max_speed, tick_rate) {
+                    (true, _) => None,
+                    (false, None) => Some(self.tick_rate),
+                    (false, tick_rate) => tick_rate,
+                };
+
+                if let Some(tick_rate) = tick_rate {
+                    self.tick_rate = tick_rate;
+                }
+
+                if tick_rate != self.sim_state.tick_rate() {
+                    self.sim_state.set_tick_rate(tick_rate);
+                }
+
+                self.show_history(ui);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.edit_mode, "Edit mode");
+
+                    ui.add_enabled_ui(self.edit_mode, |ui| {
+                        ui.checkbox(&mut self.keep_orbits, "Keep orbits");
+                    });
+                });
+
+                self.trails.show_settings(ui);
+
+                ui.horizontal(|ui| {
+                    ui.label("Adding preview");
+
+                    let mut days = self.preview_horizon / SECONDS_PER_DAY;
+                    // a zero speed would keep the value stuck at zero
+                    let speed = (days * 0.01).max(1e-3);
+
+                    let changed = ui
+                        .add(
+                            egui::DragValue::new(&mut days)
+                                .range(0.0..=100_000.0)
+                                .speed(speed)
+                                .suffix(" days"),
+                        )
+                        .changed();
+
+                    if changed {
+                        self.preview_horizon = days * SECONDS_PER_DAY;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let undo = ui.add_enabled(self.undo.can_undo(), egui::Button::new("Undo"));
+
+                    if undo.clicked() {
+                        self.undo();
+                    }
+
+                    let redo = ui.add_enabled(self.undo.can_redo(), egui::Button::new("Redo"));
+
+                    if redo.clicked() {
+                        self.redo();
+                    }
+                });
+
+                ui.separator();
+
+                let mut integrator = self.sim_state.integrator();
+
+                egui::ComboBox::from_label("Integrator")
+                    .selected_text(integrator.name())
+                    .show_ui(ui, |ui| {
+                        for kind in IntegratorKind::ALL {
+                            ui.selectable_value(&mut integrator, kind, kind.name());
+                        }
+                    });
+
+                if integrator != self.sim_state.integrator() {
+                    self.sim_state.set_integrator(integrator);
+                }
+
+                let mut step_mode = self.sim_state.step_mode();
+
+                egui::ComboBox::from_label("Step")
+                    .selected_text(step_mode.name())
+                    .show_ui(ui, |ui| {
+                        for mode in StepMode::ALL {
+                            ui.selectable_value(&mut step_mode, mode, mode.name());
+                        }
+                    });
+
+                if step_mode != self.sim_state.step_mode() {
+                    self.sim_state.set_step_mode(step_mode);
+                }
+
+                let step_label = match step_mode {
+                    StepMode::WallClock => None,
+                    StepMode::Fixed => Some("Step size"),
+                    StepMode::Adaptive => Some("Max step size"),
+                };
+
+                if let Some(step_label) = step_label {
+                    let mut fixed_step = self.sim_state.fixed_step();
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut fixed_step)
+                                .range(StepMode::STEP_RANGE)
+                                .suffix(" s"),
+                        );
+                        ui.label(step_label);
+                    });
+
+                    if fixed_step != self.sim_state.fixed_step() {
+                        self.sim_state.set_fixed_step(fixed_step);
+                    }
+                }
+
+                if step_mode == StepMode::Adaptive {
+                    let mut accuracy = self.sim_state.step_accuracy();
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut accuracy)
+                                .range(1e-4..=1.0)
+                                .speed(1e-3),
+                        );
+                        ui.label("Accuracy");
+                    });
+
+                    if accuracy != self.sim_state.step_accuracy() {
+                        self.sim_state.set_step_accuracy(accuracy);
+                    }
+                }
+
+                ui.label(format!(
+                    "Current step: {:.3} s",
+                    self.sim_state.delta_time()
+                ));
+
+                ui.separator();
+
+                let mut solver = self.sim_state.solver();
+
+                egui::ComboBox::from_label("Gravity")
+                    .selected_text(solver.name())
+                    .show_ui(ui, |ui| {
+                        for kind in SolverKind::ALL {
+                            ui.selectable_value(&mut solver, kind, kind.name());
+                        }
+                    });
+
+                if solver != self.sim_state.solver() {
+                    self.sim_state.set_solver(solver);
+                }
+
+                let mut softening = self.sim_state.softening() * KM_PER_VPX;
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut softening)
+                            .range(0.0..=1e9)
+                            .speed(100.0)
+                            .suffix(" km"),
+                    );
+                    ui.label("Softening");
+                });
+
+                if softening != self.sim_state.softening() * KM_PER_VPX {
+                    self.sim_state.set_softening(softening / KM_PER_VPX);
+                    self.accuracy_report = None;
+                }
+
+                if solver == SolverKind::BarnesHut {
+                    let mut theta = self.sim_state.theta();
+
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut theta)
+                                .range(0.0..=2.0)
+                                .speed(0.01),
+                        );
+                        ui.label("Opening angle");
+                    });
+
+                    if theta != self.sim_state.theta() {
+                        self.sim_state.set_theta(theta);
+                        self.accuracy_report = None;
+                    }
+
+                    if ui.button("Compare with direct sum").clicked() {
+                        self.accuracy_report = Some(compare_with_direct_sum(
+                            &self.frames.latest().objects,
+                            theta,
+                            self.sim_state.softening(),
+                        ));
+                    }
+
+                    if let Some(AccuracyReport { mean, max }) = self.accuracy_report {
+                        ui.label(format!(
+                            "Error: {:.3}% mean, {:.3}% max",
+                            mean * 100.,
+                            max * 100.
+                        ));
+                    }
+                }
+
+                ui.separator();
+
+                let mut response = self.sim_state.collision_response();
+
+                egui::ComboBox::from_label("Collisions")
+                    .selected_text(response.name())
+                    .show_ui(ui, |ui| {
+                        for kind in CollisionResponse::ALL {
+                            ui.selectable_value(&mut response, kind, kind.name());
+                        }
+                    });
+
+                if response != self.sim_state.collision_response() {
+                    self.sim_state.set_collision_response(response);
+                }
+
+                for (event, _) in self.collisions.iter().rev() {
+                    let [a, b] = event.masses;
+
+                    let outcome = match event.outcome {
+                        CollisionOutcome::Merged => "merged",
+                        CollisionOutcome::Bounced => "bounced",
+                        CollisionOutcome::Shattered => "shattered",
+                    };
+
+                    ui.label(format!(
+                        "{}: {a:.2e}kg and {b:.2e}kg {outcome}",
+                        format_time_ord(event.elapsed as usize)
+                    ));
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.scenario_path);
+
+                    if ui.button("Save scenario").clicked() {
+                        self.file_status = Some(match self.save_scenario(ctx) {
+                            Ok(()) => "Saved".to_owned(),
+                            Err(error) => format!("Failed to save: {error}"),
+                        });
+                    }
+                });
+
+                self.show_recording(ui);
+
+                if let Some(status) = &self.file_status {
+                    ui.label(status);
+                }
+            });
+    }
+
+    /// Side panel editing the selected body and showing its orbit
+    fn show_inspector_panel(&mut self, ctx: &egui::Context) {
+        let frame = self.frames.latest();
+        let objects = &frame.objects;
+
+        let Some(mut index) = self.selected_index(objects) else {
+            self.selected = None;
+            return;
+        };
+
+        egui::SidePanel::right("inspector")
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut id = objects[index].id;
+
+                ui.horizontal(|ui| {
+                    show_body_picker(ui, "Body", &mut id, objects);
+
+                    if ui.button("Deselect").clicked() {
+                        self.selected = None;
+                    }
+                });
+
+                if self.selected.is_none() {
+                    return;
+                }
+
+                self.selected = Some(id);
+
+                if let Some(picked) = objects.iter().position(|object| object.id == id) {
+                    index = picked;
+                }
+
+                ui.separator();
+
+                let mut body = objects[index].clone();
+
+                if show_body_editor(ui, &mut body) {
+                    if let Some(command) = Command::update(&objects[index], &body) {
+                        self.execute(ctx, command);
+                    }
+                }
+
+                ui.separator();
+
+                let Some(attractor) = dominant_attractor(objects, index) else {
+                    ui.label("Not orbiting anything");
+                    return;
+                };
+
+                ui.label(format!(
+                    "Orbiting {}",
+                    body_name(objects, objects[attractor].id)
+                ));
+
+                let Some(elements) = OrbitalElements::of(&objects[index], &objects[attractor])
+                else {
+                    return;
+                };
+
+                show_orbital_elements(ui, &elements);
+            });
+    }
+
+    /// Index of the selected body in `objects`, none if it's gone
+    fn selected_index(&self, objects: &[CosmosObject]) -> Option<usize> {
+        let selected = self.selected?;
+
+        objects.iter().position(|object| object.id == selected)
+    }
+
+    /// Selects the body under a click in the viewport, a click on empty space deselects
+    fn update_selecting(&mut self, ctx: &egui::Context) {
+        let (clicked, Some(mouse_pos)) = ctx.input(|state| {
+            (
+                state.pointer.primary_clicked(),
+                state.pointer.interact_pos(),
+            )
+        }) else {
+            return;
+        };
+
+        if !clicked || self.adding.is_some() || self.pointer_over_ui(ctx) {
+            return;
+        }
+
+        self.selected = self.body_at(mouse_pos).map(|object| object.id);
+    }
+
+    /// The body closest to a point on the screen among the ones covering it.
+    /// Small bodies are hit within a few points around them
+    fn body_at(&self, screen_pos: Pos2) -> Option<CosmosObject> {
+        const MIN_HIT_RADIUS: f32 = 6.0;
+
+        let position = (self.transform.inverse() * screen_pos).to_dvec2();
+        let min_radius = (MIN_HIT_RADIUS / self.transform.scaling) as f64;
+
+        let frame = self.frames.latest();
+
+        frame
+            .objects
+            .iter()
+            .map(|object| (object, (object.position - position).length()))
+            .filter(|(object, dist)| *dist <= object.radius.max(min_radius))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(object, _)| object.clone())
+    }
+
+    fn show_plot_window(&mut self, ctx: &egui::Context) {
+        let frame = self.frames.latest();
+
+        self.plot.sample(&frame);
+        self.plot.show(ctx, &frame.objects);
+    }
+
+    /// Conserved quantities of the latest frame and their drift,
+    /// the physics only measures them while they're shown or plotted
+    fn show_diagnostics_window(&self, ctx: &egui::Context) {
+        const M_PER_KM: f64 = 1e3;
+
+        let frame = self.frames.latest();
+        let (diagnostics, drift) = (frame.diagnostics, frame.drift);
+
+        let joules = (KM_PER_VPX * M_PER_KM).powi(2);
+        let center = diagnostics.center_of_mass * KM_PER_VPX;
+        let center_velocity = diagnostics.center_of_mass_velocity() * KM_PER_VPX;
+
+        let response = egui::Window::new("Diagnostics")
+            .resizable(false)
+            .default_open(false)
+            .default_pos(ctx.screen_rect().right_top() + Vec2::new(-250.0, 10.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("diagnostics").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    ui.label("Value");
+                    ui.label("Drift");
+                    ui.end_row();
+
+                    ui.label("Energy");
+                    ui.label(format!("{:.6e} J", diagnostics.energy() * joules));
+                    ui.label(format!("{:+.3e}", drift.energy));
+                    ui.end_row();
+
+                    ui.label("Kinetic");
+                    ui.label(format!("{:.6e} J", diagnostics.kinetic_energy * joules));
+                    ui.end_row();
+
+                    ui.label("Potential");
+                    ui.label(format!("{:.6e} J", diagnostics.potential_energy * joules));
+                    ui.end_row();
+
+                    ui.label("Momentum");
+                    ui.label(format!(
+                        "{:.6e} kg*km/sec",
+                        diagnostics.momentum.length() * KM_PER_VPX
+                    ));
+                    ui.label(format!("{:.3e}", drift.momentum));
+                    ui.end_row();
+
+                    ui.label("Angular momentum");
+                    ui.label(format!(
+                        "{:.6e} kg*km^2/sec",
+                        diagnostics.angular_momentum * KM_PER_VPX * KM_PER_VPX
+                    ));
+                    ui.label(format!("{:.3e}", drift.angular_momentum));
+                    ui.end_row();
+
+                    ui.label("Center of mass");
+                    ui.label(format!("({:.4e}, {:.4e}) km", center.x, center.y));
+                    ui.end_row();
+
+                    ui.label("Its velocity");
+                    ui.label(format!(
+                        "({:.4e}, {:.4e}) km/sec",
+                        center_velocity.x, center_velocity.y
+                    ));
+                    ui.end_row();
+                });
+            });
+
+        let expanded = response.is_some_and(|response| response.inner.is_some());
+
+        self.sim_state
+            .set_measure_diagnostics(expanded || self.plot.needs_diagnostics());
+    }
+
+    /// Controls of the trajectory recording
+    fn show_recording(&mut self, ui: &mut egui::Ui) {
+        let recorded = self.frames.recorded_samples();
+
+        ui.add_enabled_ui(recorded.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.record_interval)
+                        .range(1.0..=1e9)
+                        .suffix(" s"),
+                );
+                ui.label("Sample interval");
+            });
+
+            let mut format = self.record_format;
+
+            egui::ComboBox::from_label("Format")
+                .selected_text(format.name())
+                .show_ui(ui, |ui| {
+                    for kind in RecordFormat::ALL {
+                        ui.selectable_value(&mut format, kind, kind.name());
+                    }
+                });
+
+            if format != self.record_format {
+                self.record_format = format;
+
+                let path = Path::new(&self.record_path).with_extension(format.extension());
+                self.record_path = path.display().to_string();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.record_path);
+
+            let Some(samples) = recorded else {
+                if ui.button("Record").clicked() {
+                    let recorder = Recorder::new(self.record_interval);
+                    self.frames.start_recording(recorder);
+                    self.file_status = None;
+                }
+
+                return;
+            };
+
+            if ui.button("Stop and save").clicked() {
+                if let Some(recorder) = self.frames.stop_recording() {
+                    let result = recorder.save(&self.record_path, self.record_format);
+
+                    self.file_status = Some(match result {
+                        Ok(()) => format!("Saved {} samples", recorder.samples()),
+                        Err(error) => format!("Failed to save: {error}"),
+                    });
+                }
+            }
+
+            ui.label(format!("{samples} samples"));
+        });
+    }
+
+    /// Writes the latest frame with the current time speed and camera to `scenario_path`
+    fn save_scenario(&self, ctx: &egui::Context) -> Result<(), ScenarioError> {
+        let center = self.transform.inverse() * ctx.screen_rect().center();
+        let camera = Camera::new(center.to_dvec2(), self.transform.scaling);
+
+        Scenario::from_objects(
+            &self.frames.latest().objects,
+            self.sim_state.time_speed(),
+            camera,
+        )
+        .save(&self.scenario_path)
+    }
+
+    fn update_collisions(&mut self, ctx: &egui::Context) {
+        const MAX_LOGGED: usize = 5;
+
+        let now = ctx.input(|state| state.time);
+
+        for event in self.sim_state.take_collisions() {
+            if self.collisions.len() == MAX_LOGGED {
+                self.collisions.pop_front();
+            }
+
+            self.collisions.push_back((event, now));
+        }
+    }
+
+    /// Fading rings around the points of recent collisions
+    fn draw_collisions(&self, ctx: &egui::Context, painter: Painter) {
+        const FADE_TIME: f64 = 1.5;
+
+        let now = ctx.input(|state| state.time);
+
+        for &(event, received) in &self.collisions {
+            let age = now - received;
+
+            if age > FADE_TIME {
+                continue;
+            }
+
+            let alpha = 1.0 - (age / FADE_TIME) as f32;
+            let radius = (10.0 + 30.0 * (1.0 - alpha)) / self.transform.scaling;
+            let stroke = Stroke::new(
+                2.0 / self.transform.scaling,
+                COLLISION_COLOR.gamma_multiply(alpha),
+            );
+
+            painter.circle(
+                event.position.to_pos2(),
+                radius,
+                Color32::TRANSPARENT,
+                stroke,
+            );
+        }
+    }
+
+    /// Whether the pointer is above a window or a panel rather than the viewport
+    fn pointer_over_ui(&self, ctx: &egui::Context) -> bool {
+        let Some(pos) = ctx.input(|state| state.pointer.hover_pos()) else {
+            return false;
+        };
+
+        !self.viewport.contains(pos)
+            || ctx
+                .layer_id_at(pos)
+                .is_some_and(|layer| layer.order != egui::Order::Background)
+    }
+
+    fn update_zoom(&mut self, ctx: &egui::Context) {
+        if self.adding.is_some() || self.pointer_over_ui(ctx) {
+            return;
+        }
+
+        ctx.input(|state| {
+            for event in state.events.iter() {
+                let egui::Event::MouseWheel { delta, .. } = event else {
+                    continue;
+                };
+
+                let zoom_delta = 1.5f32.powf(delta.y);
+
+                if state.modifiers.alt {
+                    self.sim_state.zoom_time_speed(zoom_delta as f64);
+                } else if state.modifiers.shift {
+                    if let Some(quantity) = self.showed_quantity {
+                        self.quantity_scale[quantity as usize] *= zoom_delta;
+                    }
+                } else {
+                    if let Some(real_mouse_pos) = state.pointer.hover_pos() {
+                        self.zoom_relative_to(zoom_delta, real_mouse_pos);
+                    }
+                }
+            }
+        });
+    }
+
+    fn zoom_relative_to(&mut self, delta_scale: f32, point: Pos2) {
+        if delta_scale != 1.0 {
+            // mouse coords in virtual space
+            let vmouse = self.transform.inverse() * point;
+
+            // apply zoom to transform
+            self.transform.scaling *= delta_scale;
+
+            // new real coords of point, the mouse pointed to,
+            let new_rmouse = self.transform * vmouse;
+
+            // shift to which transform.translation should be shifted
+            let shift = point - new_rmouse;
+
+            // apply shift
+            self.transform.translation += shift;
+        }
+    }
+
+    fn update_moving(&mut self, ctx: &egui::Context) {
+        let (pressed, released, Some(mouse_pos)) = ctx.input(|state| {
+            (
+                state.pointer.primary_pressed(),
+                state.pointer.primary_released(),
+                state.pointer.hover_pos(),
+            )
+        }) else {
+            return;
+        };
+
+        if pressed && !self.pointer_over_ui(ctx) && self.dragging.is_none() {
+            self.moving = Some(Moving {
+                origin: mouse_pos,
+                old_translation: self.transform.translation,
+            });
+        } else if released {
+            self.moving = None;
+        }
+
+        if let Some(Moving {
+            origin,
+            old_translation,
+        }) = self.moving
+        {
+            let delta = mouse_pos - origin;
+
+            self.transform.translation = old_translation + delta;
+        }
+    }
+
+    fn update_adding(&mut self, ctx: &egui::Context) {
+        let (pressed, released, Some(mouse_pos)) = ctx.input(|state| {
+            (
+                state.pointer.secondary_pressed(),
+                state.pointer.secondary_released(),
+                state.pointer.hover_pos(),
+            )
+        }) else {
+            return;
+        };
+
+        if pressed && !self.pointer_over_ui(ctx) {
+            self.adding = Some(Adding {
+                position: mouse_pos,
+            });
+            self.preview = Some(Preview::new(self.frames.latest().objects.clone()));
+        } else if released {
+            self.preview = None;
+
+            let Some(adding) = self.adding.take() else {
+                return;
+            };
+
+            let new_object = CosmosObject {
+                id: self.frames.new_id(),
+                ..self.adding_object(adding, mouse_pos)
+            };
+
+            self.execute(ctx, Command::Add(new_object));
+        }
+    }
+
+    /// The object being added, thrown from the press point towards the opposite of the mouse
+    fn adding_object(&self, adding: Adding, mouse_pos: Pos2) -> CosmosObject {
+        let position = self.transform.inverse() * adding.position;
+        let velocity = (adding.position - mouse_pos)
+            / self.transform.scaling
+            / self.quantity_scale[PhysicalQuantity::Velocity as usize];
+
+        CosmosObject {
+            mass: self.adding_mass,
+            radius: self.adding_mass.sqrt(),
+            position: position.to_dvec2(),
+            velocity: velocity.to_dvec2(),
+            ..Default::default()
+        }
+    }
+
+    /// Drags bodies in the edit mode
+    fn update_dragging(&mut self, ctx: &egui::Context) {
+        let (pressed, down, Some(mouse_pos)) = ctx.input(|state| {
+            (
+                state.pointer.primary_pressed(),
+                state.pointer.primary_down(),
+                state.pointer.hover_pos(),
+            )
+        }) else {
+            return;
+        };
+
+        let mouse = (self.transform.inverse() * mouse_pos).to_dvec2();
+
+        if pressed && self.edit_mode && !self.pointer_over_ui(ctx) {
+            let frame = self.frames.latest();
+
+            self.dragging = self.body_at(mouse_pos).map(|body| {
+                let index = frame.objects.iter().position(|object| object.id == body.id);
+
+                Dragging {
+                    id: body.id,
+                    offset: body.position - mouse,
+                    parent: index
+                        .and_then(|index| dominant_attractor(&frame.objects, index))
+                        .map(|parent| frame.objects[parent].id),
+                }
+            });
+
+            if let Some(dragging) = self.dragging {
+                self.selected = Some(dragging.id);
+            }
+        }
+
+        if !down {
+            self.dragging = None;
+        }
+
+        let Some(dragging) = self.dragging else {
+            return;
+        };
+
+        let frame = self.frames.latest();
+        let find = |id: u64| frame.objects.iter().find(|object| object.id == id);
+
+        let Some(body) = find(dragging.id) else {
+            self.dragging = None;
+            return;
+        };
+
+        let position = mouse + dragging.offset;
+
+        if position == body.position {
+            return;
+        }
+
+        let parent = dragging.parent.filter(|_| self.keep_orbits).and_then(find);
+        let moved = moved_body(body, position, parent);
+
+        if let Some(command) = Command::update(body, &moved) {
+            self.execute(ctx, command);
+        }
+    }
+
+    /// Deletes and duplicates the selected body in the edit mode
+    fn update_editing(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (toggle, delete, duplicate) = ctx.input_mut(|state| {
+            (
+                state.key_pressed(Key::E),
+                state.key_pressed(Key::Delete),
+                state.consume_key(Modifiers::COMMAND, Key::D),
+            )
+        });
+
+        if toggle {
+            self.edit_mode = !self.edit_mode;
+        }
+
+        if !self.edit_mode {
+            return;
+        }
+
+        let frame = self.frames.latest();
+
+        let Some(index) = self.selected_index(&frame.objects) else {
+            return;
+        };
+
+        let body = &frame.objects[index];
+
+        if delete {
+            self.execute(ctx, Command::Delete(body.clone()));
+            self.selected = None;
+        } else if duplicate {
+            let copy = CosmosObject {
+                id: self.frames.new_id(),
+                position: body.position + DVec2::X * body.radius * 3.0,
+                ..body.clone()
+            };
+
+            self.selected = Some(copy.id);
+            self.execute(ctx, Command::Add(copy));
+        }
+    }
+
+    /// Undoes with Ctrl+Z and redoes with Ctrl+Shift+Z
+    fn update_undo(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        // the shifted shortcut first, the plain one matches it too
+        let (redo, undo) = ctx.input_mut(|state| {
+            (
+                state.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+                state.consume_key(Modifiers::COMMAND, Key::Z),
+            )
+        });
+
+        if redo {
+            self.redo();
+        } else if undo {
+            self.undo();
+        }
+    }
+
+    /// Applies `command` and records it for undoing
+    fn execute(&mut self, ctx: &egui::Context, command: Command) {
+        self.edit(command.apply());
+        self.undo.record(command, ctx.input(|state| state.time));
+    }
+
+    fn undo(&mut self) {
+        if let Some(edit) = self.undo.undo() {
+            self.edit(edit);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(edit) = self.undo.redo() {
+            self.edit(edit);
+        }
+    }
+
+    /// Timeline over the recorded history, scrubbing it pauses the simulation
+    fn show_history(&mut self, ui: &mut egui::Ui) {
+        let Some((first, last)) = self.frames.history_span() else {
+            return;
+        };
+
+        let mut elapsed = self.frames.latest().elapsed;
+        let last = last.max(elapsed);
+
+        ui.horizontal(|ui| {
+            let slider = egui::Slider::new(&mut elapsed, first..=last).show_value(false);
+
+            if ui.add(slider).changed() {
+                self.sim_state.set_paused(true);
+                self.edit(Edit::Rewind(elapsed));
+            }
+
+            let reverse = ui.add_enabled(self.can_reverse_time(), egui::Button::new("Reverse"));
+
+            if reverse.clicked() {
+                self.sim_state.reverse_time();
+            }
+        });
+
+        ui.label(format_time_ord(elapsed as usize));
+    }
+
+    /// Negative time speed only makes sense for time reversible integrators,
+    /// turning it back forward is always allowed
+    fn can_reverse_time(&self) -> bool {
+        self.sim_state.integrator().is_time_reversible() || self.sim_state.time_speed() < 0.0
+    }
+
+    fn update_pause(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (toggle, step, reverse) = ctx.input(|state| {
+            (
+                state.key_pressed(Key::Space),
+                state.key_pressed(Key::Period),
+                state.key_pressed(Key::R),
+            )
+        });
+
+        if toggle {
+            self.sim_state.set_paused(!self.sim_state.paused());
+        }
+
+        if reverse && self.can_reverse_time() {
+            self.sim_state.reverse_time();
+        }
+
+        if step && self.sim_state.paused() {
+            self.sim_state.request_steps(self.steps_per_click);
+        }
+    }
+
+    /// Queues `edit` for the physics thread, waking it up if paused
+    fn edit(&self, edit: Edit) {
+        self.frames.edit(edit);
+        self.sim_state.wake();
+    }
+
+    fn update_showed_quantity(&mut self, ctx: &egui::Context) {
+        self.showed_quantity = ctx.input(|state| {
+            if state.key_pressed(Key::Escape) {
+                return None;
+            }
+
+            let pressed = if state.key_pressed(Key::V) {
+                Some(PhysicalQuantity::Velocity)
+            } else if state.key_pressed(Key::I) {
+                Some(PhysicalQuantity::Impulse)
+            } else if state.key_pressed(Key::A) {
+                Some(PhysicalQuantity::Acceleration)
+            } else if state.key_pressed(Key::F) {
+                Some(PhysicalQuantity::Force)
+            } else {
+                None
+            };
+
+            if let Some(pressed) = pressed {
+                if Some(pressed) == self.showed_quantity {
+                    None
+                } else {
+                    Some(pressed)
+                }
+            } else {
+                self.showed_quantity
+            }
+        });
+    }
+
+    fn draw_grid(&self, painter: Painter, ui_size: Vec2) {
+        let start = self.transform.inverse() * Pos2::ZERO;
+        let end = self.transform.inverse() * ui_size.to_pos2();
+
+        let start = (start / self.cell_size).floor();
+        let end = (end / self.cell_size).ceil();
+
+        let stroke = Stroke::new(self.cell_size / 20.0, GRID_COLOR);
+
+        for x in start.x as isize..end.x as isize {
+            let points = [
+                Pos2::new(x as f32, start.y) * self.cell_size,
+                Pos2::new(x as f32, end.y) * self.cell_size,
+            ];
+
+            painter.line(points, stroke);
+        }
+
+        for y in start.y as isize..end.y as isize {
+            let points = [
+                Pos2::new(start.x, y as f32) * self.cell_size,
+                Pos2::new(end.x, y as f32) * self.cell_size,
+            ];
+
+            painter.line(points, stroke);
+        }
+    }
+
+    fn show_info(&self, painter: Painter, field_size: Vec2) {
+        let box_size = Vec2::new(field_size.x, field_size.y * 0.1);
+        let box_start = field_size.to_pos2() - box_size;
+
+        painter.raw.rect(
+            Rect::from_min_size(box_start, box_size),
+            Rounding::ZERO,
+            BACKGROUND_COLOR,
+            Stroke::new(1.0, GRID_COLOR),
+        );
+
+        fn draw_segment(
+            painter: &egui::Painter,
+            pos: Pos2,
+            size: f32,
+            protrusion_size: f32,
+            stroke: Stroke,
+        ) {
+            let half_protrusion = protrusion_size / 2.0;
+            let protrusion = Vec2::new(0., half_protrusion);
+
+            let points = [pos, pos + Vec2::new(size, 0.0)];
+
+            painter.line_segment(points, stroke);
+            painter.line_segment([points[0] + protrusion, points[0] - protrusion], stroke);
+            painter.line_segment([points[1] + protrusion, points[1] - protrusion], stroke);
+        }
+
+        let height = box_size.y / 3.;
+        let segment_size = self.cell_size * self.transform.scaling;
+        let protrusion_size = box_size.y / 20.;
+
+        let stroke = Stroke::new(3., GRID_COLOR);
+
+        draw_segment(
+            painter.raw,
+            box_start + Vec2::splat(box_size.y / 4.0),
+            segment_size,
+            protrusion_size,
+            stroke,
+        );
+
+        const KM_PER_PC: f64 = 30.8568e9;
+        const KM_PER_LYR: f64 = 9.460_730_472_580_8e12;
+
+        let km_on_side = self.cell_size as f64 * KM_PER_VPX;
+        let pc_on_side = km_on_side / KM_PER_PC;
+        let lyr_per_side = km_on_side / KM_PER_LYR;
+
+        let scale_info_text =
+            format!("{lyr_per_side:.2e}lyr = {pc_on_side:.2e}pc = {km_on_side:.2e}km");
+
+        let font_size = height * 0.7;
+
+        let scale_info_text_pos = box_start
+            + Vec2::new(
+                box_size.y / 2. + segment_size,
+                box_size.y * 0.3 - font_size / 2.,
+            );
+
+        let font_id = FontId::new(font_size, egui::FontFamily::Monospace);
+
+        painter.raw.text(
+            scale_info_text_pos,
+            Align2::LEFT_TOP,
+            scale_info_text,
+            font_id.clone(),
+            Color32::from_gray(150),
+        );
+
+        let elapsed_text = format_time_ord(self.frames.latest().elapsed as usize);
+
+        painter.raw.text(
+            Pos2::new(box_size.x - box_size.y / 4., scale_info_text_pos.y),
+            Align2::RIGHT_TOP,
+            elapsed_text,
+            font_id.clone(),
+            Color32::from_gray(150),
+        );
+
+        if let Some(quantity) = self.showed_quantity {
+            let stroke = Stroke::new(3., quantity.color());
+
+            draw_segment(
+                painter.raw,
+                box_start + Vec2::new(box_size.y / 4., box_size.y * 3. / 4.),
+                segment_size,
+                protrusion_size,
+                stroke,
+            );
+
+            let quantity_in_side = km_on_side / self.quantity_scale[quantity as usize] as f64;
+
+            let quantity_info_text = format!("{quantity_in_side:.2e}{}", quantity.unit_name());
+
+            let quantity_info_pos = box_start
+                + Vec2::new(
+                    box_size.y / 2. + segment_size,
+                    box_size.y * 0.7 - font_size / 2.,
+                );
+
+            painter.raw.text(
+                quantity_info_pos,
+                Align2::LEFT_TOP,
+                quantity_info_text,
+                font_id.clone(),
+                Color32::from_gray(150),
+            );
+        }
+
+        let time_speed_info_text = if self.sim_state.paused() {
+            "paused".to_owned()
+        } else {
+            let time_speed = self.sim_state.time_speed();
+            let direction = if time_speed < 0. { " back" } else { "" };
+
+            format!(
+                "{}{direction} in 1 sec",
+                format_time(time_speed.abs() as usize)
+            )
+        };
+
+        let time_speed_info_pos = box_start
+            + Vec2::new(
+                box_size.x - box_size.y / 4.,
+                box_size.y * 0.7 - font_size / 2.,
+            );
+
+        painter.raw.text(
+            time_speed_info_pos,
+            Align2::RIGHT_TOP,
+            time_speed_info_text,
+            font_id.clone(),
+            Color32::from_gray(150),
+        );
+
+        let physics_info_text = format!(
+            "{:.0} tps, {:.0}% load",
+            self.sim_state.measured_tick_rate(),
+            self.sim_state.load() * 100.
+        );
+
+        let physics_info_pos =
+            box_start + Vec2::new(box_size.x / 2., box_size.y * 0.7 - font_size / 2.);
+
+        painter.raw.text(
+            physics_info_pos,
+            Align2::CENTER_TOP,
+            physics_info_text,
+            font_id.clone(),
+            Color32::from_gray(150),
+        );
+    }
+
+    fn draw_quantity_vec(
+        &self,
+        painter: Painter,
+        quantity: PhysicalQuantity,
+        object: &CosmosObject,
+    ) {
+        let scale = self.quantity_scale[quantity as usize];
+        let vec = quantity.of(object).to_vec2() * scale;
+
+        let stroke = Stroke::new(object.radius as f32 * 0.1, quantity.color());
+
+        painter.vec(object.position.to_pos2(), vec, stroke)
+    }
+
+    fn draw_planets(&mut self, painter: Painter<'_>) {
+        let frame = self.frames.latest();
+
+        self.trails.sample(&frame);
+        self.trails.draw(painter, &frame.objects, 1.5);
+
+        for object in &frame.objects {
+            draw_object(painter, object);
+
+            if self.selected == Some(object.id) {
+                self.draw_selection(painter, object);
+            }
+
+            if let Some(quantity) = self.showed_quantity {
+                self.draw_quantity_vec(painter, quantity, object)
+            }
+        }
+    }
+
+    /// Predicted trajectory of `new_object` among the bodies present when the adding started,
+    /// ending with a ring if it hits one of them
+    fn draw_preview(&mut self, painter: Painter, new_object: &CosmosObject) {
+        let prediction = Prediction::new(self.sim_state, self.preview_horizon);
+
+        let Some(preview) = self.preview.as_mut() else {
+            return;
+        };
+
+        let trajectory = preview.trajectory(prediction, new_object);
+
+        let width = 1.0 / self.transform.scaling;
+        let points = trajectory
+            .points
+            .iter()
+            .map(|point| point.to_pos2())
+            .collect();
+
+        painter.draw(Shape::line(points, Stroke::new(width, PREVIEW_COLOR)));
+
+        if let (true, Some(end)) = (trajectory.collides, trajectory.points.last()) {
+            painter.circle(
+                end.to_pos2(),
+                new_object.radius as f32,
+                Color32::TRANSPARENT,
+                Stroke::new(width * 1.5, COLLISION_COLOR),
+            );
+        }
+    }
+
+    /// Ring around the selected body
+    fn draw_selection(&self, painter: Painter, object: &CosmosObject) {
+        let gap = 4.0 / self.transform.scaling;
+        let stroke = Stroke::new(1.5 / self.transform.scaling, SELECTION_COLOR);
+
+        painter.circle(
+            object.position.to_pos2(),
+            object.radius as f32 + gap,
+            Color32::TRANSPARENT,
+            stroke,
+        );
+    }
+
+    fn autoscale_grid(&mut self) {
+        const MAX_SIZE: f32 = 100.0;
+        const MIN_SIZE: f32 = 20.0;
+
+        if self.cell_size * self.transform.scaling > MAX_SIZE {
+            self.cell_size /= 2.0;
+        }
+
+        if self.cell_size * self.transform.scaling < MIN_SIZE {
+            self.cell_size *= 2.0;
+        }
+    }
+}
+
+/// `body` moved to `position`. With a `parent`, the orbit around it keeps its shape,
+/// it's rotated and scaled with the velocity adjusted to stay similar
+fn moved_body(body: &CosmosObject, position: DVec2, parent: Option<&CosmosObject>) -> CosmosObject {
+    let mut moved = body.clone();
+    moved.position = position;
+
+    let Some(parent) = parent else {
+        return moved;
+    };
+
+    let old = body.position - parent.position;
+    let new = position - parent.position;
+
+    if old.length_sq() == 0.0 || new.length_sq() == 0.0 {
+        return moved;
+    }
+
+    // a Kepler orbit scaled by k has velocities scaled by 1/sqrt(k) at the same anomaly
+    let scale = new.length() / old.length();
+    let rotation = new.angle() - old.angle();
+
+    let velocity = (body.velocity - parent.velocity).rotated(rotation) / scale.sqrt();
+    moved.velocity = parent.velocity + velocity;
+
+    moved
+}
+
+/// Mass, radius, position and velocity of `body`, returns whether any was changed
+fn show_body_editor(ui: &mut egui::Ui, body: &mut CosmosObject) -> bool {
+    const ANY: RangeInclusive<f64> = f64::NEG_INFINITY..=f64::INFINITY;
+    const POSITIVE: RangeInclusive<f64> = f64::MIN_POSITIVE..=f64::INFINITY;
+
+    let mut changed = false;
+
+    egui::Grid::new("body editor").show(ui, |ui| {
+        ui.label("Mass");
+        changed |= edit_value(ui, &mut body.mass, 1.0, " kg", 0.0..=f64::INFINITY);
+        ui.end_row();
+
+        ui.label("Radius");
+        changed |= edit_value(ui, &mut body.radius, KM_PER_VPX, " km", POSITIVE);
+        ui.end_row();
+
+        ui.label("Position");
+        changed |= edit_value(ui, &mut body.position.x, KM_PER_VPX, " km", ANY);
+        changed |= edit_value(ui, &mut body.position.y, KM_PER_VPX, " km", ANY);
+        ui.end_row();
+
+        ui.label("Velocity");
+        changed |= edit_value(ui, &mut body.velocity.x, KM_PER_VPX, " km/sec", ANY);
+        changed |= edit_value(ui, &mut body.velocity.y, KM_PER_VPX, " km/sec", ANY);
+        ui.end_row();
+    });
+
+    changed
+}
+
+/// Drag value showing `value` multiplied by `scale` in scientific notation,
+/// `range` bounds the shown value
+fn edit_value(
+    ui: &mut egui::Ui,
+    value: &mut f64,
+    scale: f64,
+    suffix: &str,
+    range: RangeInclusive<f64>,
+) -> bool {
+    let mut shown = *value * scale;
+    let speed = (shown.abs() * 1e-3).max(1e-3);
+
+    let response = ui.add(
+        egui::DragValue::new(&mut shown)
+            .speed(speed)
+            .range(range)
+            .custom_formatter(|value, _| format!("{value:.4e}"))
+            .custom_parser(|text| text.trim().parse().ok())
+            .suffix(suffix),
+    );
+
+    if response.changed() && shown.is_finite() {
+        *value = shown / scale;
+        true
+    } else {
+        false
+    }
+}
+
+fn show_orbital_elements(ui: &mut egui::Ui, elements: &OrbitalElements) {
+    const M_PER_KM: f64 = 1e3;
+
+    let km = |vpx: f64| format!("{:.6e} km", vpx * KM_PER_VPX);
+    let unbound = || "unbound".to_owned();
+
+    let rows = [
+        ("Semi-major axis", km(elements.semi_major_axis)),
+        ("Eccentricity", format!("{:.6}", elements.eccentricity)),
+        (
+            "Argument of periapsis",
+            format!("{:.3}°", elements.argument_of_periapsis.to_degrees()),
+        ),
+        (
+            "True anomaly",
+            format!("{:.3}°", elements.true_anomaly.to_degrees()),
+        ),
+        (
+            "Period",
+            elements
+                .period
+                .map_or_else(unbound, |period| format_time(period as usize)),
+        ),
+        ("Periapsis", km(elements.periapsis)),
+        ("Apoapsis", elements.apoapsis.map_or_else(unbound, km)),
+        (
+            "Specific energy",
+            format!(
+                "{:.6e} J/kg",
+                elements.specific_energy * (KM_PER_VPX * M_PER_KM).powi(2)
+            ),
+        ),
+    ];
+
+    egui::Grid::new("orbital elements")
+        .striped(true)
+        .show(ui, |ui| {
+            for (name, value) in rows {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+/// Combo box choosing one of `objects` by its name or id
+pub fn show_body_picker(ui: &mut egui::Ui, label: &str, id: &mut u64, objects: &[CosmosObject]) {
+    ui.label(label);
+
+    egui::ComboBox::from_id_source(label)
+        .selected_text(body_name(objects, *id))
+        .show_ui(ui, |ui| {
+            for object in objects {
+                ui.selectable_value(id, object.id, body_name(objects, object.id));
+            }
+        });
+}
+
+/// Name of the body with `id`, the id itself for unnamed ones
+pub fn body_name(objects: &[CosmosObject], id: u64) -> String {
+    match objects.iter().find(|object| object.id == id) {
+        Some(object) => match object.name.as_deref() {
+            Some(name) => name.to_owned(),
+            None => format!("#{id}"),
+        },
+        None => format!("#{id} (gone)"),
+    }
+}
+
+fn draw_object(painter: Painter, object: &CosmosObject) {
+    let [r, g, b] = object.color;
+
+    painter.circle(
+        object.position.to_pos2(),
+        object.radius as f32,
+        Color32::from_rgb(r, g, b),
+        Stroke::NONE,
+    );
+}
+
+#[derive(Clone, Copy)]
+pub struct Moving {
+    pub origin: Pos2,
+    pub old_translation: Vec2,
+}
+
+#[derive(Clone, Copy)]
+pub struct Adding {
+    pub position: Pos2,
+}
+
+/// Trajectory of the body being added, predicted against a frozen copy of the system
+pub struct Preview {
+    system: Vec<CosmosObject>,
+
+    /// the body and the prediction `trajectory` was computed for
+    computed: Option<(CosmosObject, Prediction)>,
+    trajectory: Trajectory,
+}
+
+impl Preview {
+    fn new(system: Vec<CosmosObject>) -> Self {
+        Self {
+            system,
+            computed: None,
+            trajectory: Trajectory::default(),
+        }
+    }
+
+    /// Trajectory of `body`, computed again only when it or `prediction` changed
+    fn trajectory(&mut self, prediction: Prediction, body: &CosmosObject) -> &Trajectory {
+        let up_to_date = self
+            .computed
+            .as_ref()
+            .is_some_and(|(computed, computed_prediction)| {
+                computed.position == body.position
+                    && computed.velocity == body.velocity
+                    && computed.mass == body.mass
+                    && computed.radius == body.radius
+                    && *computed_prediction == prediction
+            });
+
+        if !up_to_date {
+            let mut objects = self.system.clone();
+            objects.push(body.clone());
+
+            self.trajectory = prediction.trajectory(objects, self.system.len());
+            self.computed = Some((body.clone(), prediction));
+        }
+
+        &self.trajectory
+    }
+}
+
+/// Body being dragged in the edit mode
+#[derive(Clone, Copy)]
+pub struct Dragging {
+    pub id: u64,
+    /// from the pointer to the body, in vpx
+    pub offset: DVec2,
+    /// attractor the orbit is kept around
+    pub parent: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PhysicalQuantity {
+    Velocity = 0,
+    Impulse = 1,
+    Acceleration = 2,
+    Force = 3,
+}
+
+impl PhysicalQuantity {
+    pub const ALL: [Self; 4] = [
+        Self::Velocity,
+        Self::Impulse,
+        Self::Acceleration,
+        Self::Force,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PhysicalQuantity::Velocity => "Speed",
+            PhysicalQuantity::Impulse => "Momentum",
+            PhysicalQuantity::Acceleration => "Acceleration",
+            PhysicalQuantity::Force => "Force",
+        }
+    }
+
+    pub fn of(self, object: &CosmosObject) -> DVec2 {
+        match self {
+            PhysicalQuantity::Velocity => object.velocity,
+            PhysicalQuantity::Impulse => object.velocity * object.mass,
+            PhysicalQuantity::Acceleration => object.acceleration,
+            PhysicalQuantity::Force => object.acceleration * object.mass,
+        }
+    }
+
+    pub fn color(self) -> Color32 {
+        match self {
+            PhysicalQuantity::Velocity => Color32::LIGHT_RED,
+            PhysicalQuantity::Impulse => Color32::from_rgb(156, 245, 255),
+            PhysicalQuantity::Acceleration => Color32::LIGHT_GREEN,
+            PhysicalQuantity::Force => Color32::from_rgb(250, 255, 105),
+        }
+    }
+
+    pub fn unit_name(self) -> &'static str {
+        match self {
+            PhysicalQuantity::Velocity => "km/sec",
+            PhysicalQuantity::Impulse => "kg*km/sec",
+            PhysicalQuantity::Acceleration => "km/sec^2",
+            PhysicalQuantity::Force => "kg*km/sec^2",
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use celestial::physics::{IntegratorKind, StepMode};
+
+/// N-body gravity simulator
+#[derive(Parser)]
+#[command(version)]
+pub struct Args {
+    /// RON scenario to load, the Sun and the Earth if not given
+    pub scenario: Option<PathBuf>,
+
+    /// semi-implicit-euler, leapfrog, velocity-verlet or runge-kutta-4
+    #[arg(long, value_parser = parse_integrator)]
+    pub integrator: Option<IntegratorKind>,
+
+    /// wall-clock, fixed or adaptive
+    #[arg(long, value_parser = parse_step_mode)]
+    pub step_mode: Option<StepMode>,
+
+    /// Step size in seconds, the largest allowed one in the adaptive mode
+    #[arg(long, value_name = "SECONDS", value_parser = parse_step)]
+    pub step: Option<f64>,
+
+    /// Simulated seconds in one real second, overrides the scenario
+    #[arg(long, value_name = "SECONDS", allow_negative_numbers = true, value_parser = parse_finite)]
+    pub time_speed: Option<f64>,
+
+    /// Window width in points
+    #[arg(long, default_value_t = 800.0)]
+    pub width: f32,
+
+    /// Window height in points
+    #[arg(long, default_value_t = 800.0)]
+    pub height: f32,
+
+    /// Run without a window for `--duration` and write the final state
+    #[arg(long, requires = "duration")]
+    pub headless: bool,
+
+    /// Simulated seconds to run in the headless mode
+    #[arg(long, value_name = "SECONDS", requires = "headless", value_parser = parse_non_negative)]
+    pub duration: Option<f64>,
+
+    /// Scenario file for the final state of the headless mode, stdout if not given
+    #[arg(long, value_name = "FILE", requires = "headless")]
+    pub output: Option<PathBuf>,
+
+    /// File for the states sampled during the headless mode, CSV if it ends with .csv
+    /// and columnar otherwise
+    #[arg(long, value_name = "FILE", requires_all = ["headless", "sample_interval"])]
+    pub samples: Option<PathBuf>,
+
+    /// Simulated seconds between two samples
+    #[arg(long, value_name = "SECONDS", requires = "samples", value_parser = parse_positive)]
+    pub sample_interval: Option<f64>,
+}
+
+/// Finite number above zero, an interval of zero would never advance the time
+fn parse_positive(value: &str) -> Result<f64, String> {
+    let value = parse_finite(value)?;
+
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("expected a positive number, got {value}"))
+    }
+}
+
+/// Finite number not below zero, the headless mode can't run backwards
+fn parse_non_negative(value: &str) -> Result<f64, String> {
+    let value = parse_finite(value)?;
+
+    if value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("expected a number not below zero, got {value}"))
+    }
+}
+
+/// Step size the simulation controls accept, see [`StepMode::STEP_RANGE`]
+fn parse_step(value: &str) -> Result<f64, String> {
+    let value = parse_finite(value)?;
+    let range = StepMode::STEP_RANGE;
+
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "expected a step between {} and {} seconds, got {value}",
+            range.start(),
+            range.end()
+        ))
+    }
+}
+
+fn parse_finite(value: &str) -> Result<f64, String> {
+    let value: f64 = value.parse().map_err(|error| format!("{error}"))?;
+
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(format!("expected a finite number, got {value}"))
+    }
+}
+
+fn parse_integrator(name: &str) -> Result<IntegratorKind, String> {
+    parse_kind(name, IntegratorKind::ALL, IntegratorKind::name)
+}
+
+fn parse_step_mode(name: &str) -> Result<StepMode, String> {
+    parse_kind(name, StepMode::ALL, StepMode::name)
+}
+
+/// Finds the kind whose display name in kebab case is `name`
+fn parse_kind<T: Copy>(
+    name: &str,
+    all: impl IntoIterator<Item = T>,
+    display_name: fn(T) -> &'static str,
+) -> Result<T, String> {
+    let all = all
+        .into_iter()
+        .map(|kind| (kebab_case(display_name(kind)), kind))
+        .collect::<Vec<_>>();
+
+    all.iter()
+        .find(|(kebab, _)| kebab == name)
+        .map(|&(_, kind)| kind)
+        .ok_or_else(|| {
+            let names = all.iter().map(|(kebab, _)| kebab.as_str());
+
+            format!("expected one of {}", names.collect::<Vec<_>>().join(", "))
+        })
+}
+
+fn kebab_case(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
+}
@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use clap::Parser;
+use eframe::NativeOptions;
+use egui::emath::TSTransform;
+use egui::{Vec2, ViewportBuilder};
+
+use celestial::cosmos_object::CosmosObject;
+use celestial::headless::{Batch, BatchError};
+use celestial::physics::{CollisionResponse, FrameBuffer, IntegratorKind, Physics};
+use celestial::physics::{SimulationState, SolverKind, StepMode};
+use celestial::scenario::{Scenario, ScenarioError, DEFAULT_SCENARIO};
+
+use app::App;
+use cli::Args;
+use utils::ToEgui;
+
+mod app;
+mod cli;
+mod plot;
+mod trails;
+mod undo;
+mod utils;
+
+pub fn main() -> eframe::Result {
+    // virtual day in real second
+    static SIM_STATE: SimulationState = SimulationState::new(60. * 60. * 24.)
+        .with_integrator(IntegratorKind::VelocityVerlet)
+        .with_step_mode(StepMode::Fixed, 60.)
+        .with_solver(SolverKind::DirectSum, 0.5)
+        .with_collision_response(CollisionResponse::Merge)
+        .with_tick_rate(240.);
+
+    let args = Args::parse();
+
+    let (scenario, objects) = match load_scenario(args.scenario.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("failed to load the scenario: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    SIM_STATE.set_time_speed(args.time_speed.unwrap_or(scenario.time_speed));
+
+    if let Some(integrator) = args.integrator {
+        SIM_STATE.set_integrator(integrator);
+    }
+
+    if let Some(step_mode) = args.step_mode {
+        SIM_STATE.set_step_mode(step_mode);
+    }
+
+    if let Some(step) = args.step {
+        SIM_STATE.set_fixed_step(step);
+    }
+
+    let frames = Arc::new(FrameBuffer::new(objects));
+
+    let mut physics = Physics::new(Arc::clone(&frames), &SIM_STATE);
+
+    if args.headless {
+        let batch = Batch {
+            duration: args.duration.unwrap_or_default(),
+            samples: args.samples.zip(args.sample_interval),
+        };
+
+        let result = batch.run(&mut physics, scenario.camera).and_then(|result| {
+            match &args.output {
+                Some(path) => result.save(path),
+                None => print_scenario(&result),
+            }
+            .map_err(BatchError::from)
+        });
+
+        if let Err(error) = result {
+            eprintln!("failed to write the results: {error}");
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    thread::spawn(move || physics.start());
+
+    let size = Vec2::new(args.width, args.height);
+
+    eframe::run_native(
+        "celestial.rs",
+        NativeOptions {
+            window_builder: Some(Box::new(move |_| {
+                ViewportBuilder::default().with_inner_size(size)
+            })),
+            ..Default::default()
+        },
+        Box::new(move |ctx| {
+            let mut fonts = egui::FontDefinitions::default();
+
+            fonts.font_data.insert(
+                "segoe".to_owned(),
+                egui::FontData::from_static(include_bytes!("../assets/Segoe UI.ttf")),
+            );
+
+            fonts
+                .families
+                .entry(egui::FontFamily::Proportional)
+                .or_default()
+                .insert(0, "segoe".to_owned());
+
+            fonts
+                .families
+                .entry(egui::FontFamily::Monospace)
+                .or_default()
+                .push("segoe".to_owned());
+
+            ctx.egui_ctx.set_fonts(fonts);
+
+            let zoom = scenario.camera.zoom;
+            let center = scenario.camera.center().to_pos2();
+            let transform = TSTransform::new(size / 2.0 - center.to_vec2() * zoom, zoom);
+
+            let scenario_path = save_path(args.scenario.as_deref()).display().to_string();
+
+            Ok(Box::new(App::new(
+                frames,
+                transform,
+                &SIM_STATE,
+                scenario_path,
+            )))
+        }),
+    )
+}
+
+/// Scenario from `path`, the built-in one if there is none
+fn load_scenario(path: Option<&Path>) -> Result<(Scenario, Vec<CosmosObject>), ScenarioError> {
+    let scenario = match path {
+        Some(path) => Scenario::load(path)?,
+        None => Scenario::parse(DEFAULT_SCENARIO)?,
+    };
+
+    let objects = scenario.objects()?;
+
+    Ok((scenario, objects))
+}
+
+/// Default file of the save button, next to the loaded scenario but never over it
+fn save_path(loaded: Option<&Path>) -> PathBuf {
+    match loaded {
+        Some(path) => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+            path.with_file_name(format!("{stem}-saved.ron"))
+        }
+        None => PathBuf::from("scenario.ron"),
+    }
+}
+
+/// Writes `scenario` to stdout, a reader closing the pipe early like `head` isn't an error
+fn print_scenario(scenario: &Scenario) -> Result<(), ScenarioError> {
+    let ron = scenario.to_ron()?;
+
+    match writeln!(io::stdout().lock(), "{ron}") {
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => Err(error.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,195 @@
+use egui_plot::{Line, Plot, PlotPoints};
+
+use celestial::cosmos_object::CosmosObject;
+use celestial::physics::{Frame, KM_PER_VPX};
+
+use crate::app::{show_body_picker, PhysicalQuantity};
+use crate::utils::SECONDS_PER_DAY;
+
+/// Quantity graphed against the simulated time
+#[derive(Clone, Copy, PartialEq)]
+pub enum Series {
+    /// magnitude of a quantity of the body with the given id
+    Magnitude(PhysicalQuantity, u64),
+    /// between the bodies with the given ids
+    Distance(u64, u64),
+    EnergyDrift,
+}
+
+impl Series {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Magnitude(quantity, _) => quantity.name(),
+            Self::Distance(..) => "Distance",
+            Self::EnergyDrift => "Energy drift",
+        }
+    }
+
+    fn unit_name(self) -> &'static str {
+        match self {
+            Self::Magnitude(quantity, _) => quantity.unit_name(),
+            Self::Distance(..) => "km",
+            Self::EnergyDrift => "relative",
+        }
+    }
+
+    /// Value in `frame`, none if a body is missing, so the series ends when it disappears
+    fn value(self, frame: &Frame) -> Option<f64> {
+        let body = |id: u64| frame.objects.iter().find(|object| object.id == id);
+
+        match self {
+            Self::Magnitude(quantity, id) => Some(quantity.of(body(id)?).length() * KM_PER_VPX),
+            Self::Distance(a, b) => {
+                Some((body(b)?.position - body(a)?.position).length() * KM_PER_VPX)
+            }
+            Self::EnergyDrift => Some(frame.drift.energy),
+        }
+    }
+}
+
+/// Window graphing a [`Series`] sampled from every new frame
+pub struct PlotWindow {
+    series: Series,
+
+    /// simulated days and values
+    points: Vec<[f64; 2]>,
+}
+
+impl PlotWindow {
+    /// Points kept at most, the older half is thinned out when it's reached
+    const MAX_POINTS: usize = 20_000;
+
+    pub fn new() -> Self {
+        Self {
+            series: Series::EnergyDrift,
+            points: Vec::new(),
+        }
+    }
+
+    /// Whether the series is measured by the physics only on request,
+    /// see [`celestial::physics::SimulationState::measures_diagnostics`]
+    pub fn needs_diagnostics(&self) -> bool {
+        self.series == Series::EnergyDrift
+    }
+
+    /// Appends the value of the series in `frame` if it's newer than the last point.
+    /// Points later than `frame` belong to a rewound future and are dropped
+    pub fn sample(&mut self, frame: &Frame) {
+        // the drift only changes with a fresh measurement, plotted at the time it was taken
+        let elapsed = match self.series {
+            Series::EnergyDrift => frame.measured,
+            _ => frame.elapsed,
+        };
+
+        let day = elapsed / SECONDS_PER_DAY;
+
+        while self.points.last().is_some_and(|&[last, _]| last > day) {
+            self.points.pop();
+        }
+
+        if self.points.last().is_some_and(|&[last, _]| last == day) {
+            return;
+        }
+
+        if let Some(value) = self.series.value(frame) {
+            self.points.push([day, value]);
+        }
+
+        if self.points.len() == Self::MAX_POINTS {
+            self.thin_out();
+        }
+    }
+
+    /// Drops every second point of the older half
+    fn thin_out(&mut self) {
+        let half = self.points.len() / 2;
+
+        let mut index = 0;
+
+        self.points.retain(|_| {
+            index += 1;
+            index > half || index % 2 == 0
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, objects: &[CosmosObject]) {
+        egui::Window::new("Plot")
+            .default_open(false)
+            .default_size([400.0, 250.0])
+            .show(ctx, |ui| {
+                let mut series = self.series;
+
+                ui.horizontal(|ui| {
+                    show_series_picker(ui, &mut series, objects);
+
+                    match &mut series {
+                        Series::Magnitude(_, id) => {
+                            show_body_picker(ui, "of", id, objects);
+                        }
+                        Series::Distance(a, b) => {
+                            show_body_picker(ui, "from", a, objects);
+                            show_body_picker(ui, "to", b, objects);
+                        }
+                        Series::EnergyDrift => {}
+                    }
+
+                    if ui.button("Clear").clicked() {
+                        self.points.clear();
+                    }
+                });
+
+                if series != self.series {
+                    self.series = series;
+                    self.points.clear();
+                }
+
+                let unit_name = self.series.unit_name();
+
+                Plot::new("plot")
+                    .x_axis_label("days")
+                    .y_axis_label(unit_name)
+                    .label_formatter(move |_, point| {
+                        format!("{:.3} days\n{:.6e} {unit_name}", point.x, point.y)
+                    })
+                    .show(ui, |plot_ui| {
+                        let points = PlotPoints::from(self.points.clone());
+
+                        plot_ui.line(Line::new(points).name(self.series.name()));
+                    });
+            });
+    }
+}
+
+fn show_series_picker(ui: &mut egui::Ui, series: &mut Series, objects: &[CosmosObject]) {
+    // the first body and the first other one than it
+    let other = |a: u64| {
+        objects
+            .iter()
+            .map(|object| object.id)
+            .find(|&id| id != a)
+            .unwrap_or(a)
+    };
+
+    let (a, b) = match *series {
+        Series::Magnitude(_, id) => (id, other(id)),
+        Series::Distance(a, b) => (a, b),
+        Series::EnergyDrift => {
+            let a = objects.first().map_or(0, |object| object.id);
+
+            (a, other(a))
+        }
+    };
+
+    let choices = PhysicalQuantity::ALL
+        .map(|quantity| Series::Magnitude(quantity, a))
+        .into_iter()
+        .chain([Series::Distance(a, b), Series::EnergyDrift]);
+
+    egui::ComboBox::from_id_source("series")
+        .selected_text(series.name())
+        .show_ui(ui, |ui| {
+            for choice in choices {
+                ui.selectable_value(series, choice, choice.name());
+            }
+        });
+}
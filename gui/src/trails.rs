@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use egui::{Color32, Stroke};
+
+use celestial::cosmos_object::CosmosObject;
+use celestial::math::DVec2;
+use celestial::physics::Frame;
+
+use crate::utils::{Painter, ToEgui, SECONDS_PER_DAY};
+
+/// How much of its past path a trail keeps
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrailLength {
+    /// simulated seconds
+    Duration(f64),
+    /// positions, one per sampled frame
+    Points(usize),
+}
+
+impl TrailLength {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Duration(_) => "Duration",
+            Self::Points(_) => "Points",
+        }
+    }
+}
+
+/// Past positions of every body sampled from the latest frames
+pub struct Trails {
+    pub visible: bool,
+    pub length: TrailLength,
+
+    /// simulated seconds and positions by body id, oldest first
+    trails: HashMap<u64, VecDeque<(f64, DVec2)>>,
+    last_elapsed: Option<f64>,
+}
+
+impl Trails {
+    /// Positions kept per body at most whatever the length
+    const MAX_POINTS: usize = 10_000;
+
+    /// Segments drawn per body at most, longer trails skip the points in between
+    const MAX_SEGMENTS: usize = 256;
+
+    /// Opacity of the newest segment
+    const ALPHA: f32 = 0.8;
+
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            length: TrailLength::Duration(30.0 * SECONDS_PER_DAY),
+            trails: HashMap::new(),
+            last_elapsed: None,
+        }
+    }
+
+    /// Appends the positions in `frame` if it's a new one.
+    /// Positions later than `frame` belong to a rewound future and are dropped
+    pub fn sample(&mut self, frame: &Frame) {
+        if self.last_elapsed == Some(frame.elapsed) {
+            return;
+        }
+
+        self.last_elapsed = Some(frame.elapsed);
+
+        let alive: HashSet<u64> = frame.objects.iter().map(|object| object.id).collect();
+
+        self.trails.retain(|id, _| alive.contains(id));
+
+        for object in &frame.objects {
+            let trail = self.trails.entry(object.id).or_default();
+
+            while trail.back().is_some_and(|&(time, _)| time > frame.elapsed) {
+                trail.pop_back();
+            }
+
+            trail.push_back((frame.elapsed, object.position));
+
+            let max_points = match self.length {
+                TrailLength::Duration(duration) => {
+                    let start = frame.elapsed - duration;
+
+                    while trail.front().is_some_and(|&(time, _)| time < start) {
+                        trail.pop_front();
+                    }
+
+                    Self::MAX_POINTS
+                }
+                TrailLength::Points(points) => points.min(Self::MAX_POINTS),
+            };
+
+            while trail.len() > max_points {
+                trail.pop_front();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.trails.clear();
+    }
+
+    /// Trails of `objects` fading out towards their oldest end,
+    /// `width` is in screen points
+    pub fn draw(&self, painter: Painter, objects: &[CosmosObject], width: f32) {
+        if !self.visible {
+            return;
+        }
+
+        let width = width / painter.transform.scaling;
+
+        for object in objects {
+            let Some(trail) = self.trails.get(&object.id) else {
+                continue;
+            };
+
+            let stride = trail.len().div_ceil(Self::MAX_SEGMENTS).max(1);
+
+            // the newest position is always kept so the trail reaches the body
+            let points: Vec<_> = trail
+                .iter()
+                .rev()
+                .step_by(stride)
+                .map(|&(_, position)| position.to_pos2())
+                .collect();
+
+            let [r, g, b] = object.color;
+            let color = Color32::from_rgb(r, g, b);
+            let segments = points.len().saturating_sub(1);
+
+            for (index, pair) in points.windows(2).enumerate() {
+                let alpha = Self::ALPHA * (1.0 - index as f32 / segments as f32);
+
+                painter.line(
+                    [pair[1], pair[0]],
+                    Stroke::new(width, color.gamma_multiply(alpha)),
+                );
+            }
+        }
+    }
+
+    pub fn show_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.visible, "Trails");
+
+            ui.add_enabled_ui(self.visible, |ui| {
+                let mut length = self.length;
+
+                let choices = [
+                    TrailLength::Duration(30.0 * SECONDS_PER_DAY),
+                    TrailLength::Points(1000),
+                ];
+
+                egui::ComboBox::from_id_source("trail_length")
+                    .selected_text(length.name())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for choice in choices {
+                            let selected = choice.name() == length.name();
+
+                            if ui.selectable_label(selected, choice.name()).clicked() && !selected {
+                                length = choice;
+                            }
+                        }
+                    });
+
+                match &mut length {
+                    TrailLength::Duration(duration) => {
+                        let mut days = *duration / SECONDS_PER_DAY;
+                        let speed = days * 0.01;
+
+                        let changed = ui
+                            .add(
+                                egui::DragValue::new(&mut days)
+                                    .range(0.01..=100_000.0)
+                                    .speed(speed)
+                                    .suffix(" days"),
+                            )
+                            .changed();
+
+                        if changed {
+                            *duration = days * SECONDS_PER_DAY;
+                        }
+                    }
+                    TrailLength::Points(points) => {
+                        ui.add(
+                            egui::DragValue::new(points)
+                                .range(2..=Self::MAX_POINTS)
+                                .suffix(" points"),
+                        );
+                    }
+                }
+
+                self.length = length;
+
+                if ui.button("Clear").clicked() {
+                    self.clear();
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,122 @@
+use celestial::cosmos_object::{BodyPatch, CosmosObject};
+use celestial::physics::Edit;
+
+/// Reversible change of the system made from the UI
+pub enum Command {
+    Add(CosmosObject),
+    Delete(CosmosObject),
+    /// moves and property edits of the body with the given id,
+    /// only the changed properties are restored
+    Update {
+        id: u64,
+        before: BodyPatch,
+        after: BodyPatch,
+    },
+}
+
+impl Command {
+    /// Update changing `before` into `after`, none if they don't differ
+    pub fn update(before: &CosmosObject, after: &CosmosObject) -> Option<Self> {
+        let patch = BodyPatch::between(before, after);
+
+        (!patch.is_empty()).then(|| Self::Update {
+            id: before.id,
+            before: BodyPatch::between(after, before),
+            after: patch,
+        })
+    }
+
+    pub fn apply(&self) -> Edit {
+        match self {
+            Self::Add(object) => Edit::Add(object.clone()),
+            Self::Delete(object) => Edit::Remove(object.id),
+            Self::Update { id, after, .. } => Edit::Update(*id, *after),
+        }
+    }
+
+    pub fn revert(&self) -> Edit {
+        match self {
+            Self::Add(object) => Edit::Remove(object.id),
+            Self::Delete(object) => Edit::Add(object.clone()),
+            Self::Update { id, before, .. } => Edit::Update(*id, *before),
+        }
+    }
+}
+
+/// Done and undone commands, newest last
+#[derive(Default)]
+pub struct UndoStack {
+    /// with the time they were recorded at
+    done: Vec<(Command, f64)>,
+    undone: Vec<Command>,
+}
+
+impl UndoStack {
+    /// Updates of the same body closer in time than this are a single command,
+    /// so a drag is undone at once
+    const COALESCE_TIME: f64 = 0.5;
+
+    const CAPACITY: usize = 256;
+
+    /// Records an applied `command`, dropping the undone ones
+    pub fn record(&mut self, command: Command, time: f64) {
+        self.undone.clear();
+
+        if let (
+            &Command::Update { id, before, after },
+            Some((
+                Command::Update {
+                    id: last_id,
+                    before: last_before,
+                    after: last_after,
+                },
+                last_time,
+            )),
+        ) = (&command, self.done.last_mut())
+        {
+            if *last_id == id && time - *last_time < Self::COALESCE_TIME {
+                // the newest values to apply, the oldest ones to restore
+                *last_after = after.or(*last_after);
+                *last_before = last_before.or(before);
+                *last_time = time;
+
+                return;
+            }
+        }
+
+        if self.done.len() == Self::CAPACITY {
+            self.done.remove(0);
+        }
+
+        self.done.push((command, time));
+    }
+
+    /// Edit reverting the last done command
+    pub fn undo(&mut self) -> Option<Edit> {
+        let (command, _) = self.done.pop()?;
+        let edit = command.revert();
+
+        self.undone.push(command);
+
+        Some(edit)
+    }
+
+    /// Edit applying the last undone command again
+    pub fn redo(&mut self) -> Option<Edit> {
+        let command = self.undone.pop()?;
+        let edit = command.apply();
+
+        // never coalesced with the following commands
+        self.done.push((command, f64::NEG_INFINITY));
+
+        Some(edit)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+}
@@ -0,0 +1,35 @@
+use egui::{Pos2, Vec2};
+
+use celestial::math::DVec2;
+
+/// Narrowing of the physics vectors to egui ones, only done on the way to the screen
+pub trait ToEgui {
+    fn to_pos2(self) -> Pos2;
+    fn to_vec2(self) -> Vec2;
+}
+
+impl ToEgui for DVec2 {
+    fn to_pos2(self) -> Pos2 {
+        Pos2::new(self.x as f32, self.y as f32)
+    }
+
+    fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+}
+
+pub trait ToDVec2 {
+    fn to_dvec2(self) -> DVec2;
+}
+
+impl ToDVec2 for Pos2 {
+    fn to_dvec2(self) -> DVec2 {
+        DVec2::new(self.x as f64, self.y as f64)
+    }
+}
+
+impl ToDVec2 for Vec2 {
+    fn to_dvec2(self) -> DVec2 {
+        DVec2::new(self.x as f64, self.y as f64)
+    }
+}
@@ -0,0 +1,7 @@
+mod convert;
+mod painter;
+mod time;
+
+pub use convert::{ToDVec2, ToEgui};
+pub use painter::Painter;
+pub use time::{format_time, format_time_ord, SECONDS_PER_DAY};
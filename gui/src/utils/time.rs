@@ -3,6 +3,8 @@ const HOUR: usize = MINUTE * 60;
 const DAY: usize = HOUR * 24;
 const YEAR: usize = DAY * 365;
 
+pub const SECONDS_PER_DAY: f64 = DAY as f64;
+
 pub fn format_time_ord(seconds: usize) -> String {
     let year = seconds / YEAR;
     let day = seconds % YEAR / DAY;
@@ -1,36 +1,67 @@
-use egui::{Color32, Pos2, Stroke, Vec2};
+use std::sync::Arc;
 
-use crate::app::PhysicalQuantity;
+use crate::math::DVec2;
+use crate::orbit::KeplerOrbit;
 use crate::physics::GRAVITIONAL_CONST;
-use crate::utils::Painter;
 
-#[derive(Default)]
+/// Color of objects not given one
+pub const DEFAULT_COLOR: [u8; 3] = [160, 160, 160];
+
+#[derive(Clone)]
 pub struct CosmosObject {
+    /// unique within a simulation, 0 until the physics assigns one
+    pub id: u64,
+
+    pub name: Option<Arc<str>>,
+
+    /// sRGB
+    pub color: [u8; 3],
+
     /// mass in kilograms
-    pub mass: f32,
+    pub mass: f64,
 
     /// radius in vpx
-    pub radius: f32,
+    pub radius: f64,
 
     /// position in vpx
-    pub position: Pos2,
+    pub position: DVec2,
 
     /// velocity in vpx/sec
-    pub velocity: Vec2,
+    pub velocity: DVec2,
 
     /// acceleration in vpx/sec^2
-    pub acceleration: Vec2,
+    pub acceleration: DVec2,
+}
+
+impl Default for CosmosObject {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: None,
+            color: DEFAULT_COLOR,
+            mass: 0.0,
+            radius: 0.0,
+            position: DVec2::ZERO,
+            velocity: DVec2::ZERO,
+            acceleration: DVec2::ZERO,
+        }
+    }
 }
 
 impl CosmosObject {
-    pub fn draw(&self, painter: Painter) {
-        painter.circle(self.position, self.radius, Color32::GRAY, Stroke::NONE);
+    /// Whether the state holds no NaNs or infinities
+    pub fn is_finite(&self) -> bool {
+        self.mass.is_finite()
+            && self.radius.is_finite()
+            && self.position.is_finite()
+            && self.velocity.is_finite()
+            && self.acceleration.is_finite()
     }
 
-    pub fn orbit(mut self, other: &Self, orbit_radius: f32, anomaly: f32, dir: f32) -> Self {
+    pub fn orbit(mut self, other: &Self, orbit_radius: f64, anomaly: f64, dir: f64) -> Self {
         let speed = (GRAVITIONAL_CONST * other.mass / orbit_radius).sqrt();
 
-        let u = Vec2::angled(anomaly);
+        let u = DVec2::angled(anomaly);
 
         self.position = other.position + u * orbit_radius;
         self.velocity = dir * u.rot90() * speed;
@@ -38,12 +69,76 @@ impl CosmosObject {
         self
     }
 
-    pub fn get_quantity(&self, quantity: PhysicalQuantity) -> Vec2 {
-        match quantity {
-            PhysicalQuantity::Velocity => self.velocity,
-            PhysicalQuantity::Impulse => self.velocity * self.mass,
-            PhysicalQuantity::Acceleration => self.acceleration,
-            PhysicalQuantity::Force => self.acceleration * self.mass,
+    /// Places the object on `orbit` around `parent`, moving along with it
+    pub fn on_orbit(mut self, parent: &Self, orbit: &KeplerOrbit) -> Self {
+        let mu = GRAVITIONAL_CONST * (parent.mass + self.mass);
+        let (position, velocity) = orbit.state(mu);
+
+        self.position = parent.position + position;
+        self.velocity = parent.velocity + velocity;
+
+        self
+    }
+}
+
+/// New values of some properties of a body, the others keep their current ones
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct BodyPatch {
+    pub mass: Option<f64>,
+    pub radius: Option<f64>,
+    pub position: Option<DVec2>,
+    pub velocity: Option<DVec2>,
+}
+
+impl BodyPatch {
+    /// Properties of `after` that differ from `before`
+    pub fn between(before: &CosmosObject, after: &CosmosObject) -> Self {
+        fn changed<T: PartialEq + Copy>(before: T, after: T) -> Option<T> {
+            (before != after).then_some(after)
+        }
+
+        Self {
+            mass: changed(before.mass, after.mass),
+            radius: changed(before.radius, after.radius),
+            position: changed(before.position, after.position),
+            velocity: changed(before.velocity, after.velocity),
+        }
+    }
+
+    pub fn apply(&self, object: &mut CosmosObject) {
+        if let Some(mass) = self.mass {
+            object.mass = mass;
         }
+
+        if let Some(radius) = self.radius {
+            object.radius = radius;
+        }
+
+        if let Some(position) = self.position {
+            object.position = position;
+        }
+
+        if let Some(velocity) = self.velocity {
+            object.velocity = velocity;
+        }
+    }
+
+    /// Properties of this patch, the ones of `other` where this one has none
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            mass: self.mass.or(other.mass),
+            radius: self.radius.or(other.radius),
+            position: self.position.or(other.position),
+            velocity: self.velocity.or(other.velocity),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether it changes the motion of the system and not only how a body looks or collides
+    pub fn changes_dynamics(&self) -> bool {
+        self.mass.is_some() || self.position.is_some() || self.velocity.is_some()
     }
 }
@@ -0,0 +1,81 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::physics::Physics;
+use crate::recorder::{RecordFormat, Recorder};
+use crate::scenario::{Camera, Scenario, ScenarioError};
+
+/// Run of the physics without a window, as fast as the machine allows
+pub struct Batch {
+    /// simulated seconds to run for
+    pub duration: f64,
+
+    /// file for the samples and the simulated seconds between two of them,
+    /// CSV if it ends with `.csv` and columnar otherwise, see [`RecordFormat`]
+    pub samples: Option<(PathBuf, f64)>,
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    Io(io::Error),
+    Scenario(ScenarioError),
+    /// the time would never advance past a sample
+    InvalidSampleInterval(f64),
+}
+
+impl Batch {
+    /// Drives `physics` for the whole duration and returns the final state as a scenario
+    pub fn run(&self, physics: &mut Physics, camera: Camera) -> Result<Scenario, BatchError> {
+        match &self.samples {
+            Some((path, interval)) => {
+                if interval.is_nan() || *interval <= 0.0 {
+                    return Err(BatchError::InvalidSampleInterval(*interval));
+                }
+
+                let mut recorder = Recorder::new(*interval);
+
+                let Ok(()) = physics.run_sampled(self.duration, *interval, |frame| {
+                    recorder.record(frame);
+                    Ok::<_, Infallible>(())
+                });
+
+                recorder.save(path, RecordFormat::from_path(path))?;
+            }
+            None => physics.run_for(self.duration),
+        }
+
+        Ok(Scenario::from_objects(
+            &physics.frames.latest().objects,
+            physics.sim_state.time_speed(),
+            camera,
+        ))
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Scenario(error) => write!(f, "{error}"),
+            Self::InvalidSampleInterval(interval) => {
+                write!(f, "sample interval must be positive, got {interval}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<io::Error> for BatchError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ScenarioError> for BatchError {
+    fn from(error: ScenarioError) -> Self {
+        Self::Scenario(error)
+    }
+}
@@ -0,0 +1,10 @@
+//! Simulation core of celestial: bodies, gravity, integrators and the physics thread,
+//! independent of any user interface
+
+pub mod cosmos_object;
+pub mod headless;
+pub mod math;
+pub mod orbit;
+pub mod physics;
+pub mod recorder;
+pub mod scenario;
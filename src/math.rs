@@ -0,0 +1,166 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Double precision 2D vector the physics runs on, used both for points and directions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DVec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl DVec2 {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+    pub const X: Self = Self::new(1.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0);
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn splat(v: f64) -> Self {
+        Self::new(v, v)
+    }
+
+    /// Unit vector rotated by `angle` radians from the x axis
+    pub fn angled(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Self::new(cos, sin)
+    }
+
+    pub fn length(self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn length_sq(self) -> f64 {
+        self.dot(self)
+    }
+
+    /// Unit vector of the same direction, zero stays zero
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+
+        if length == 0.0 {
+            self
+        } else {
+            self / length
+        }
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// z component of the 3D cross product
+    pub fn cross(self, other: Self) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// The same rotation by 90 degrees as `egui::Vec2::rot90`
+    pub fn rot90(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// Angle from the x axis in radians
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// Rotated by `angle` in the same direction as [`DVec2::angle`] grows
+    pub fn rotated(self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn max_elem(self) -> f64 {
+        self.x.max(self.y)
+    }
+
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+impl Add for DVec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for DVec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for DVec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for DVec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for DVec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f64> for DVec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Mul<DVec2> for f64 {
+    type Output = DVec2;
+
+    fn mul(self, rhs: DVec2) -> DVec2 {
+        rhs * self
+    }
+}
+
+impl MulAssign<f64> for DVec2 {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<f64> for DVec2 {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl DivAssign<f64> for DVec2 {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = *self / rhs;
+    }
+}
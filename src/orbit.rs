@@ -0,0 +1,373 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::cosmos_object::CosmosObject;
+use crate::math::DVec2;
+use crate::physics::GRAVITIONAL_CONST;
+
+/// Position of a body along its orbit, in radians from the periapsis
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anomaly {
+    /// grows uniformly with time, see [`Anomaly::to_true`]
+    Mean(f64),
+    True(f64),
+}
+
+/// Orbit to place a body on, see [`CosmosObject::on_orbit`].
+///
+/// Lengths are in vpx and angles in radians, measured in the direction of the motion
+#[derive(Clone, Copy, Debug)]
+pub struct KeplerOrbit {
+    /// negative for hyperbolic orbits, parabolic ones are not supported
+    pub semi_major_axis: f64,
+
+    pub eccentricity: f64,
+
+    /// angle of the periapsis from the x axis
+    pub argument_of_periapsis: f64,
+
+    pub anomaly: Anomaly,
+
+    /// 1 for the direction of [`CosmosObject::orbit`] with `dir` of 1, -1 for the opposite one
+    pub direction: f64,
+}
+
+/// Keplerian elements of the orbit of a body around an attractor, in vpx, seconds and radians.
+///
+/// Angles are measured in the direction of the motion
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitalElements {
+    /// negative for hyperbolic orbits
+    pub semi_major_axis: f64,
+
+    pub eccentricity: f64,
+
+    /// angle of the periapsis from the x axis, zero for circular orbits
+    pub argument_of_periapsis: f64,
+
+    /// angle of the body from the periapsis
+    pub true_anomaly: f64,
+
+    /// 1 for the direction of [`CosmosObject::orbit`] with `dir` of 1, -1 for the opposite one
+    pub direction: f64,
+
+    /// none for unbound orbits
+    pub period: Option<f64>,
+
+    pub periapsis: f64,
+
+    /// none for unbound orbits
+    pub apoapsis: Option<f64>,
+
+    /// orbital energy per unit of mass, negative for bound orbits
+    pub specific_energy: f64,
+}
+
+impl OrbitalElements {
+    /// Eccentricity below which the orbit is considered circular
+    const CIRCULAR: f64 = 1e-10;
+
+    /// Elements of the two body orbit of `body` relative to `attractor`,
+    /// none if they're at the same point
+    pub fn of(body: &CosmosObject, attractor: &CosmosObject) -> Option<Self> {
+        let mu = GRAVITIONAL_CONST * (body.mass + attractor.mass);
+
+        let position = body.position - attractor.position;
+        let velocity = body.velocity - attractor.velocity;
+
+        let dist = position.length();
+
+        if dist == 0.0 || mu <= 0.0 {
+            return None;
+        }
+
+        let angular_momentum = position.cross(velocity);
+        let direction = if angular_momentum > 0.0 { -1.0 } else { 1.0 };
+
+        let speed_sq = velocity.length_sq();
+        let specific_energy = speed_sq / 2.0 - mu / dist;
+
+        let eccentricity_vec =
+            (position * (speed_sq - mu / dist) - velocity * position.dot(velocity)) / mu;
+        let eccentricity = eccentricity_vec.length();
+
+        let semi_major_axis = -mu / (2.0 * specific_energy);
+
+        // a circular orbit has no periapsis, the anomaly is counted from the x axis instead
+        let (argument_of_periapsis, true_anomaly) = if eccentricity < Self::CIRCULAR {
+            (0.0, -direction * position.angle())
+        } else {
+            let from_periapsis = eccentricity_vec
+                .cross(position)
+                .atan2(eccentricity_vec.dot(position));
+
+            (eccentricity_vec.angle(), -direction * from_periapsis)
+        };
+
+        let bound = specific_energy < 0.0;
+
+        Some(Self {
+            semi_major_axis,
+            eccentricity,
+            argument_of_periapsis: argument_of_periapsis.rem_euclid(TAU),
+            true_anomaly: true_anomaly.rem_euclid(TAU),
+            direction,
+            period: bound.then(|| TAU * (semi_major_axis.powi(3) / mu).sqrt()),
+            periapsis: angular_momentum.powi(2) / (mu * (1.0 + eccentricity)),
+            apoapsis: bound.then_some(semi_major_axis * (1.0 + eccentricity)),
+            specific_energy,
+        })
+    }
+}
+
+impl Anomaly {
+    /// Newton iterations solving the Kepler equation
+    const ITERATIONS: usize = 50;
+
+    /// True anomaly of an orbit with the given `eccentricity`
+    pub fn to_true(self, eccentricity: f64) -> f64 {
+        let mean = match self {
+            Self::True(anomaly) => return anomaly,
+            Self::Mean(anomaly) => anomaly,
+        };
+
+        let e = eccentricity;
+
+        if e < 1.0 {
+            let mut eccentric = if e > 0.8 { std::f64::consts::PI } else { mean };
+
+            for _ in 0..Self::ITERATIONS {
+                let delta = (eccentric - e * eccentric.sin() - mean) / (1.0 - e * eccentric.cos());
+                eccentric -= delta;
+
+                if delta.abs() < 1e-14 {
+                    break;
+                }
+            }
+
+            let (sin, cos) = (eccentric / 2.0).sin_cos();
+
+            2.0 * ((1.0 + e).sqrt() * sin).atan2((1.0 - e).sqrt() * cos)
+        } else {
+            let mut hyperbolic = (mean / e).asinh();
+
+            for _ in 0..Self::ITERATIONS {
+                let delta =
+                    (e * hyperbolic.sinh() - hyperbolic - mean) / (e * hyperbolic.cosh() - 1.0);
+                hyperbolic -= delta;
+
+                if delta.abs() < 1e-14 {
+                    break;
+                }
+            }
+
+            2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (hyperbolic / 2.0).tanh()).atan()
+        }
+    }
+}
+
+impl KeplerOrbit {
+    /// Circular orbit of radius `radius`
+    pub fn circular(radius: f64, anomaly: f64, direction: f64) -> Self {
+        Self {
+            semi_major_axis: radius,
+            eccentricity: 0.0,
+            argument_of_periapsis: 0.0,
+            anomaly: Anomaly::True(anomaly),
+            direction,
+        }
+    }
+
+    /// Why the orbit has no finite state, none if it has one
+    pub fn invalid_reason(&self) -> Option<&'static str> {
+        let (a, e) = (self.semi_major_axis, self.eccentricity);
+
+        if !e.is_finite() || e < 0.0 {
+            return Some("the eccentricity must be a finite number of at least 0");
+        }
+
+        if e == 1.0 {
+            return Some("parabolic orbits are not supported");
+        }
+
+        if !a.is_finite() || a == 0.0 {
+            return Some("the semi-major axis must be a finite number other than 0");
+        }
+
+        if e < 1.0 && a < 0.0 {
+            return Some("elliptic orbits need a positive semi-major axis");
+        }
+
+        if e > 1.0 && a > 0.0 {
+            return Some("hyperbolic orbits need a negative semi-major axis");
+        }
+
+        if let (true, Anomaly::True(anomaly)) = (e > 1.0, self.anomaly) {
+            // the body is at infinity on the asymptotes and beyond them on the other branch
+            let asymptote = (-1.0 / e).acos();
+            let anomaly = (anomaly + PI).rem_euclid(TAU) - PI;
+
+            if anomaly.abs() >= asymptote {
+                return Some("the true anomaly must lie between the asymptotes");
+            }
+        }
+
+        None
+    }
+
+    /// Position and velocity relative to the attractor,
+    /// `mu` is the gravitational constant times the mass of both bodies
+    pub fn state(&self, mu: f64) -> (DVec2, DVec2) {
+        let e = self.eccentricity;
+        let true_anomaly = self.anomaly.to_true(e);
+
+        // semi-latus rectum, positive for both elliptic and hyperbolic orbits
+        let p = self.semi_major_axis * (1.0 - e * e);
+
+        let dist = p / (1.0 + e * true_anomaly.cos());
+        let speed = (mu / p).sqrt();
+
+        // the angles grow clockwise in the coordinates of `DVec2` for the direction of 1
+        let sign = -self.direction.signum();
+
+        let (sin, cos) = true_anomaly.sin_cos();
+
+        let position = DVec2::new(dist * cos, sign * dist * sin);
+        let velocity = DVec2::new(-speed * sin, sign * speed * (e + cos));
+
+        (
+            position.rotated(self.argument_of_periapsis),
+            velocity.rotated(self.argument_of_periapsis),
+        )
+    }
+}
+
+/// Index of the body pulling the one at `index` the most among the more massive ones,
+/// none for the most massive body
+pub fn dominant_attractor(objects: &[CosmosObject], index: usize) -> Option<usize> {
+    let body = &objects[index];
+
+    objects
+        .iter()
+        .enumerate()
+        .filter(|&(other, attractor)| other != index && attractor.mass > body.mass)
+        .map(|(other, attractor)| {
+            let dist_sq = (attractor.position - body.position).length_sq();
+
+            (other, attractor.mass / dist_sq)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(other, _)| other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::DVec2;
+
+    fn round_trip(orbit: KeplerOrbit) -> OrbitalElements {
+        let parent = CosmosObject {
+            mass: 2e30,
+            position: DVec2::new(10.0, -20.0),
+            velocity: DVec2::new(1e-4, 2e-4),
+            ..Default::default()
+        };
+        let body = CosmosObject {
+            mass: 6e24,
+            ..Default::default()
+        }
+        .on_orbit(&parent, &orbit);
+
+        OrbitalElements::of(&body, &parent).unwrap()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{a} != {b}");
+    }
+
+    #[test]
+    fn circular_orbit_elements() {
+        let sun = CosmosObject {
+            mass: 2e30,
+            ..Default::default()
+        };
+        let earth = CosmosObject {
+            mass: 6e24,
+            ..Default::default()
+        }
+        .orbit(&sun, 1496.0, 0.5, 1.0);
+
+        let elements = OrbitalElements::of(&earth, &sun).unwrap();
+        let mu = GRAVITIONAL_CONST * (sun.mass + earth.mass);
+
+        // `orbit` ignores the mass of the body, so the orbit is only nearly circular
+        assert!(elements.eccentricity < 1e-5);
+        assert!((elements.semi_major_axis / 1496.0 - 1.0).abs() < 1e-5);
+        assert_close(
+            elements.period.unwrap(),
+            TAU * (elements.semi_major_axis.powi(3) / mu).sqrt(),
+        );
+        assert!(elements.periapsis <= elements.apoapsis.unwrap());
+    }
+
+    #[test]
+    fn dominant_attractor_is_the_strongest_heavier_pull() {
+        let objects = [
+            CosmosObject {
+                mass: 2e30,
+                ..Default::default()
+            },
+            CosmosObject {
+                mass: 6e24,
+                position: DVec2::new(1496.0, 0.0),
+                ..Default::default()
+            },
+            CosmosObject {
+                mass: 7e22,
+                position: DVec2::new(1496.4, 0.0),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(dominant_attractor(&objects, 0), None);
+        assert_eq!(dominant_attractor(&objects, 1), Some(0));
+        assert_eq!(dominant_attractor(&objects, 2), Some(1));
+    }
+
+    #[test]
+    fn elements_round_trip() {
+        for direction in [1.0, -1.0] {
+            for (semi_major_axis, eccentricity) in [(1496.0, 0.3), (-800.0, 1.7)] {
+                let orbit = KeplerOrbit {
+                    semi_major_axis,
+                    eccentricity,
+                    argument_of_periapsis: 1.2,
+                    anomaly: Anomaly::True(0.7),
+                    direction,
+                };
+
+                let elements = round_trip(orbit);
+
+                assert_close(elements.semi_major_axis, semi_major_axis);
+                assert_close(elements.eccentricity, eccentricity);
+                assert_close(elements.argument_of_periapsis, 1.2);
+                assert_close(elements.true_anomaly, 0.7);
+                assert_eq!(elements.direction, direction);
+            }
+        }
+    }
+
+    #[test]
+    fn mean_anomaly_matches_true_anomaly() {
+        let orbit = KeplerOrbit {
+            semi_major_axis: 1496.0,
+            eccentricity: 0.5,
+            argument_of_periapsis: 0.0,
+            anomaly: Anomaly::Mean(2.0),
+            direction: 1.0,
+        };
+
+        let elements = round_trip(orbit);
+
+        assert_close(elements.true_anomaly, Anomaly::Mean(2.0).to_true(0.5));
+    }
+}
@@ -1,85 +1,618 @@
+mod barnes_hut;
+mod collision;
+mod diagnostics;
+mod frame;
 mod gravity;
+mod history;
+mod integrator;
+mod prediction;
 mod simulation_state;
+mod step_controller;
 
-use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use egui::Vec2;
-
-use crate::cosmos_object::CosmosObject;
+use crate::cosmos_object::{BodyPatch, CosmosObject};
 
+pub use barnes_hut::{compare_with_direct_sum, AccuracyReport};
+pub use collision::{CollisionEvent, CollisionOutcome, CollisionResponse};
+pub use diagnostics::{Diagnostics, Drift};
+pub use frame::{Edit, Frame, FrameBuffer};
+pub use gravity::{GravitySolver, SolverKind};
+pub use history::History;
+pub use integrator::{Integrator, IntegratorKind};
+pub use prediction::{Prediction, Trajectory};
 pub use simulation_state::SimulationState;
+pub use step_controller::{StepController, StepMode};
+
+/// Upper bound of steps taken in one iteration, the rest of the backlog is dropped
+const MAX_SUBSTEPS: usize = 10_000;
+
+/// Published frames between two measurements of the diagnostics
+const DIAGNOSTICS_INTERVAL: usize = 16;
 
-pub const KM_PER_VPX: f32 = 1e5;
-pub const GRAVITIONAL_CONST: f32 = 6.674e-20 / KM_PER_VPX / KM_PER_VPX / KM_PER_VPX;
+pub const KM_PER_VPX: f64 = 1e5;
+pub const GRAVITIONAL_CONST: f64 = 6.674e-20 / KM_PER_VPX / KM_PER_VPX / KM_PER_VPX;
 
 pub struct Physics {
-    pub objects: Arc<RwLock<Vec<RwLock<CosmosObject>>>>,
+    pub frames: Arc<FrameBuffer>,
 
     pub sim_state: &'static SimulationState,
 
-    delta_time: f32,
+    delta_time: f64,
+
+    /// simulated time not yet covered by steps
+    backlog: f64,
+
+    integrator_kind: IntegratorKind,
+    integrator: Box<dyn Integrator>,
+
+    solver_kind: SolverKind,
+    theta: f64,
+    softening: f64,
+    solver: Box<dyn GravitySolver>,
+
+    /// the only mutable copy of the objects, readers get it through [`FrameBuffer`]
+    objects: Vec<CosmosObject>,
+    /// whether the objects changed since the last published frame
+    dirty: bool,
+    /// whether a step was taken since the last published frame
+    stepped: bool,
+
+    /// reference of the published drift, none until measured again after an edit
+    initial: Option<Diagnostics>,
+    /// last measurement, see [`Frame::diagnostics`]
+    diagnostics: Diagnostics,
+    /// elapsed time of the last measurement
+    measured: f64,
+    drift: Drift,
+    /// frames published since the last measurement
+    unmeasured: usize,
+
+    collisions: Vec<CollisionEvent>,
 }
 
 impl Physics {
-    pub fn new(
-        objects: Arc<RwLock<Vec<RwLock<CosmosObject>>>>,
-        sim_state: &'static SimulationState,
-    ) -> Self {
+    pub fn new(frames: Arc<FrameBuffer>, sim_state: &'static SimulationState) -> Self {
+        let integrator_kind = sim_state.integrator();
+        let solver_kind = sim_state.solver();
+        let theta = sim_state.theta();
+        let softening = sim_state.softening();
+
+        let mut solver = solver_kind.create(theta, softening);
+        let mut objects = frames.latest().objects.clone();
+
+        // the first frame shows the accelerations too, like the ones after a step
+        solver.accelerate(&mut objects);
+
+        let diagnostics = Diagnostics::measure(&objects, softening);
+
         Self {
-            objects,
+            frames,
             sim_state,
             delta_time: 0.0,
+            backlog: 0.0,
+            integrator_kind,
+            integrator: integrator_kind.create(),
+            solver_kind,
+            theta,
+            softening,
+            solver,
+            objects,
+            // publishes the accelerations and the diagnostics of the first frame
+            dirty: true,
+            stepped: false,
+            initial: Some(diagnostics),
+            diagnostics,
+            measured: sim_state.elapsed(),
+            drift: Drift::default(),
+            unmeasured: 0,
+            collisions: Vec::new(),
         }
     }
 
     pub fn start(&mut self) {
+        self.sim_state.set_physics_thread();
+
+        let mut real_delta_time = 0.0;
+        let mut stats = TickStats::default();
+
         loop {
             let iter_start = Instant::now();
 
-            self.sim_state.set_delta_time(self.delta_time);
-            self.sim_state.update_elapsed(self.delta_time);
-
             let time_speed = self.sim_state.time_speed();
 
-            self.update();
+            self.sync_integrator();
+            self.sync_solver();
+            self.apply_edits();
+
+            if self.sim_state.paused() {
+                let steps = self.sim_state.take_pending_steps();
+
+                if steps == 0 {
+                    if self.dirty {
+                        self.publish();
+                    }
+
+                    self.sim_state.set_tick_stats(0.0, 0.0);
+                    stats = TickStats::default();
+
+                    // woken up by `SimulationState::wake`, the time spent here is not owed
+                    thread::park();
 
-            self.delta_time = iter_start.elapsed().as_secs_f32() * time_speed;
+                    real_delta_time = 0.0;
+                    self.backlog = 0.0;
+
+                    continue;
+                }
+
+                for _ in 0..steps {
+                    self.step_once();
+                }
+            } else {
+                let owed = if time_speed < 0.0 && !self.integrator_kind.is_time_reversible() {
+                    0.0
+                } else {
+                    real_delta_time * time_speed
+                };
+
+                match self.sim_state.step_mode() {
+                    StepMode::WallClock => self.advance_wall_clock(owed),
+                    StepMode::Fixed => self.advance_fixed(self.sim_state.fixed_step(), owed),
+                    StepMode::Adaptive => self.advance_adaptive(owed),
+                }
+            }
+
+            if self.dirty {
+                self.publish();
+            }
+
+            let busy = iter_start.elapsed();
+
+            if let Some(tick_rate) = self.sim_state.tick_rate() {
+                let period = Duration::from_secs_f64(1.0 / tick_rate);
+
+                if let Some(idle) = period.checked_sub(busy) {
+                    thread::sleep(idle);
+                }
+            }
+
+            let total = iter_start.elapsed();
+
+            if let Some((tick_rate, load)) = stats.record(busy, total) {
+                self.sim_state.set_tick_stats(tick_rate, load);
+            }
+
+            real_delta_time = total.as_secs_f64();
         }
     }
 
-    pub fn update(&self) {
-        let objects = self.objects.read().unwrap();
+    /// Takes a single step of the current step mode, the wall clock mode uses the fixed step.
+    /// The step goes back in time if the time speed is negative and the integrator allows it
+    pub fn step_once(&mut self) {
+        let step = self.next_step();
 
-        for i in 0..objects.len() {
-            let current = &objects[i];
-            let mut current = current.write().unwrap();
+        let backwards =
+            self.sim_state.time_speed() < 0.0 && self.integrator_kind.is_time_reversible();
 
-            current.acceleration = Vec2::ZERO;
+        let step = if backwards { -step } else { step };
 
-            for j in 0..objects.len() {
-                if i == j {
-                    continue;
+        self.delta_time = step;
+        self.sim_state.set_delta_time(step);
+
+        self.substep(step);
+    }
+
+    /// Advances the simulation by `duration` of simulated time as fast as possible
+    /// and publishes the result, the wall clock mode takes fixed steps
+    pub fn run_for(&mut self, duration: f64) {
+        let Ok(()) = self.run_sampled(duration, f64::INFINITY, |_| Ok::<_, Infallible>(()));
+    }
+
+    /// Same as [`Self::run_for`], also publishing the frame at the start, the one at the end
+    /// and one after every step crossing a multiple of `interval` past the start,
+    /// each one handed to `sample`. Only the last step is cut short,
+    /// so the trajectories don't depend on `interval`
+    pub fn run_sampled<E>(
+        &mut self,
+        duration: f64,
+        interval: f64,
+        mut sample: impl FnMut(&Frame) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.sync_integrator();
+        self.sync_solver();
+        self.apply_edits();
+
+        let start = self.sim_state.elapsed();
+        let end = start + duration;
+        let mut next_sample = start + interval;
+
+        self.publish();
+        sample(&self.frames.latest())?;
+
+        loop {
+            let remaining = end - self.sim_state.elapsed();
+
+            if remaining <= 0.0 {
+                break;
+            }
+
+            let step = self.next_step().min(remaining);
+
+            self.delta_time = step;
+            self.substep(step);
+
+            let elapsed = self.sim_state.elapsed();
+
+            if elapsed >= next_sample && elapsed < end {
+                // a step longer than the interval crosses several multiples
+                next_sample = start + ((elapsed - start) / interval).floor() * interval + interval;
+
+                self.sim_state.set_delta_time(step);
+                self.publish();
+                sample(&self.frames.latest())?;
+            }
+        }
+
+        if self.sim_state.elapsed() == start {
+            return Ok(());
+        }
+
+        self.sim_state.set_delta_time(self.delta_time);
+
+        self.publish();
+        sample(&self.frames.latest())
+    }
+
+    /// Length of the next step of the current step mode, the wall clock mode uses the fixed step
+    fn next_step(&self) -> f64 {
+        match self.sim_state.step_mode() {
+            StepMode::WallClock | StepMode::Fixed => self.sim_state.fixed_step(),
+            StepMode::Adaptive => {
+                StepController::new(self.sim_state.step_accuracy(), self.sim_state.fixed_step())
+                    .step(&self.objects, self.delta_time.abs())
+            }
+        }
+    }
+
+    /// Takes as many steps of `step` as fit into the simulated time owed,
+    /// so the trajectories only depend on `step` and not on the wall clock
+    fn advance_fixed(&mut self, step: f64, owed: f64) {
+        self.backlog += owed;
+
+        let substeps = ((self.backlog.abs() / step) as usize).min(MAX_SUBSTEPS);
+        let step = step.copysign(self.backlog);
+
+        if substeps == MAX_SUBSTEPS {
+            self.backlog = 0.0;
+        } else {
+            self.backlog -= substeps as f64 * step;
+        }
+
+        self.delta_time = step;
+        self.sim_state.set_delta_time(step);
+
+        self.update(substeps);
+    }
+
+    /// Takes a single step covering all the simulated time owed
+    fn advance_wall_clock(&mut self, owed: f64) {
+        self.backlog = 0.0;
+        self.delta_time = owed;
+
+        self.sim_state.set_delta_time(owed);
+
+        self.update(1);
+    }
+
+    /// Takes steps chosen by a [`StepController`] while they fit into the simulated time owed
+    fn advance_adaptive(&mut self, owed: f64) {
+        let controller =
+            StepController::new(self.sim_state.step_accuracy(), self.sim_state.fixed_step());
+
+        self.backlog += owed;
+
+        let mut substeps = 0;
+
+        loop {
+            let step = controller.step(&self.objects, self.delta_time.abs());
+
+            if step > self.backlog.abs() {
+                break;
+            }
+
+            let step = step.copysign(self.backlog);
+
+            if substeps == MAX_SUBSTEPS {
+                self.backlog = 0.0;
+                break;
+            }
+
+            self.substep(step);
+
+            self.backlog -= step;
+            self.delta_time = step;
+            substeps += 1;
+        }
+
+        self.sim_state.set_delta_time(self.delta_time);
+    }
+
+    /// Advances the objects by `substeps` steps of the current delta time
+    pub fn update(&mut self, substeps: usize) {
+        for _ in 0..substeps {
+            self.substep(self.delta_time);
+        }
+    }
+
+    /// Integrates the objects over `step` and resolves collisions
+    fn substep(&mut self, step: f64) {
+        self.integrator
+            .step(&mut self.objects, step, self.solver.as_mut());
+
+        self.dirty = true;
+        self.stepped = true;
+
+        self.sim_state.update_elapsed(step);
+
+        let count = self.objects.len();
+
+        // a body broken by an overflow would spread NaNs to every other one on the next step
+        self.objects.retain(CosmosObject::is_finite);
+
+        collision::resolve(
+            &mut self.objects,
+            self.sim_state.collision_response(),
+            self.sim_state.elapsed(),
+            &mut self.collisions,
+        );
+
+        // merges, bounces and fragments move bodies outside of the step
+        if self.objects.len() != count || !self.collisions.is_empty() {
+            self.reset_integrator();
+        }
+
+        if !self.collisions.is_empty() {
+            // fragments are new objects
+            self.assign_ids();
+
+            self.sim_state.push_collisions(self.collisions.drain(..));
+        }
+    }
+
+    fn apply_edits(&mut self) {
+        for edit in self.frames.take_edits() {
+            match edit {
+                Edit::Add(object) => {
+                    self.objects.push(object);
+                    self.reset_integrator();
+                    self.reset_drift();
+                }
+                Edit::Update(id, patch) => self.update_object(id, patch),
+                Edit::Remove(id) => {
+                    self.objects.retain(|object| object.id != id);
+                    self.reset_integrator();
+                    self.reset_drift();
                 }
+                Edit::Rewind(elapsed) => self.rewind(elapsed),
+            }
+
+            self.dirty = true;
+        }
 
-                let other = &objects[j];
-                let other = other.read().unwrap();
+        self.assign_ids();
+    }
+
+    fn update_object(&mut self, id: u64, patch: BodyPatch) {
+        let Some(target) = self.objects.iter_mut().find(|target| target.id == id) else {
+            return;
+        };
+
+        patch.apply(target);
+
+        if patch.changes_dynamics() {
+            self.reset_integrator();
+            self.reset_drift();
+        }
+    }
 
-                gravity::gravity(&mut current, &other);
+    /// Gives ids to the objects without one
+    fn assign_ids(&mut self) {
+        for object in &mut self.objects {
+            if object.id == 0 {
+                object.id = self.frames.new_id();
             }
+        }
+    }
 
-            current.acceleration *= GRAVITIONAL_CONST;
+    /// Restores the recorded frame closest to `elapsed`,
+    /// the recorded future is dropped once a step is taken from there
+    fn rewind(&mut self, elapsed: f64) {
+        let Some(frame) = self.frames.history_at(elapsed) else {
+            return;
+        };
+
+        self.objects.clone_from(&frame.objects);
+        self.sim_state.set_elapsed(frame.elapsed);
+
+        self.backlog = 0.0;
+        self.reset_integrator();
+    }
 
-            let delta_velocity = current.acceleration * self.delta_time;
-            current.velocity += delta_velocity;
+    /// Makes the current objects visible to the readers
+    fn publish(&mut self) {
+        let due = self.initial.is_none() || self.unmeasured >= DIAGNOSTICS_INTERVAL;
+
+        if due && self.sim_state.measures_diagnostics() {
+            self.diagnostics = Diagnostics::measure(&self.objects, self.softening);
+
+            let initial = *self.initial.get_or_insert(self.diagnostics);
+
+            self.drift = self.diagnostics.drift(&initial);
+            self.measured = self.sim_state.elapsed();
+            self.unmeasured = 0;
+        } else {
+            self.unmeasured += 1;
         }
 
-        for i in 0..objects.len() {
-            let current = &objects[i];
-            let mut current = current.write().unwrap();
+        let frame = Frame {
+            objects: self.objects.clone(),
+            elapsed: self.sim_state.elapsed(),
+            diagnostics: self.diagnostics,
+            measured: self.measured,
+            drift: self.drift,
+        };
 
-            let delta_position = current.velocity * self.delta_time;
-            current.position += delta_position;
+        self.frames.publish(frame, self.stepped);
+
+        self.dirty = false;
+        self.stepped = false;
+    }
+
+    /// Measures the drift from the next measured state on
+    fn reset_drift(&mut self) {
+        self.initial = None;
+    }
+
+    /// Switches to the integrator requested through [`SimulationState`]
+    fn sync_integrator(&mut self) {
+        let requested = self.sim_state.integrator();
+
+        if requested != self.integrator_kind {
+            self.integrator_kind = requested;
+            self.integrator = requested.create();
+        }
+    }
+
+    /// Switches to the gravity solver requested through [`SimulationState`]
+    fn sync_solver(&mut self) {
+        let requested = (
+            self.sim_state.solver(),
+            self.sim_state.theta(),
+            self.sim_state.softening(),
+        );
+
+        if requested != (self.solver_kind, self.theta, self.softening) {
+            let softened = requested.2 != self.softening;
+
+            (self.solver_kind, self.theta, self.softening) = requested;
+            self.solver = self.solver_kind.create(self.theta, self.softening);
+            self.reset_integrator();
+
+            // the potential energy depends on the softening
+            if softened {
+                self.reset_drift();
+            }
         }
     }
+
+    /// Drops the accelerations the integrator may keep, they're stale
+    /// once the objects or the gravity change outside of its steps
+    fn reset_integrator(&mut self) {
+        self.integrator = self.integrator_kind.create();
+    }
+}
+
+/// Measures the loop of [`Physics::start`] over windows of [`TickStats::WINDOW`]
+#[derive(Default)]
+struct TickStats {
+    ticks: u32,
+    busy: Duration,
+    total: Duration,
+}
+
+impl TickStats {
+    const WINDOW: Duration = Duration::from_millis(500);
+
+    /// Returns ticks per second and the busy share of the window once it's full
+    fn record(&mut self, busy: Duration, total: Duration) -> Option<(f64, f64)> {
+        self.ticks += 1;
+        self.busy += busy;
+        self.total += total;
+
+        if self.total < Self::WINDOW {
+            return None;
+        }
+
+        let total = self.total.as_secs_f64();
+        let stats = (self.ticks as f64 / total, self.busy.as_secs_f64() / total);
+
+        *self = Self::default();
+
+        Some(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static WHOLE: SimulationState = SimulationState::new(1.0)
+        .with_integrator(IntegratorKind::VelocityVerlet)
+        .with_step_mode(StepMode::Fixed, 600.0);
+
+    static SPLIT: SimulationState = SimulationState::new(1.0)
+        .with_integrator(IntegratorKind::VelocityVerlet)
+        .with_step_mode(StepMode::Fixed, 600.0);
+
+    fn physics(sim_state: &'static SimulationState) -> Physics {
+        let sun = CosmosObject {
+            mass: 2e30,
+            radius: 7.0,
+            ..Default::default()
+        };
+        let earth = CosmosObject {
+            mass: 6e24,
+            radius: 0.06,
+            ..Default::default()
+        }
+        .orbit(&sun, 1496.0, 0.0, 1.0);
+        let moon = CosmosObject {
+            mass: 7e22,
+            radius: 0.02,
+            ..Default::default()
+        }
+        .orbit(&earth, 3.84, 1.0, 1.0);
+
+        Physics::new(
+            Arc::new(FrameBuffer::new(vec![sun, earth, moon])),
+            sim_state,
+        )
+    }
+
+    /// Bits of the positions and velocities, equal only for identical trajectories
+    fn state(physics: &Physics) -> Vec<[u64; 4]> {
+        physics
+            .objects
+            .iter()
+            .map(|object| {
+                [
+                    object.position.x.to_bits(),
+                    object.position.y.to_bits(),
+                    object.velocity.x.to_bits(),
+                    object.velocity.y.to_bits(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fixed_steps_do_not_depend_on_how_the_time_is_owed() {
+        let mut whole = physics(&WHOLE);
+        let mut split = physics(&SPLIT);
+
+        let before = state(&whole);
+
+        // 40 steps of 600 seconds, owed at once or in uneven parts
+        whole.advance_fixed(600.0, 24_000.0);
+
+        for owed in [150.0, 1350.0, 4500.0, 37.5, 262.5, 17_700.0] {
+            split.advance_fixed(600.0, owed);
+        }
+
+        assert_ne!(state(&whole), before);
+        assert_eq!(state(&whole), state(&split));
+    }
 }
@@ -0,0 +1,308 @@
+use crate::math::DVec2;
+
+use crate::cosmos_object::CosmosObject;
+
+use super::gravity::{pull, DirectSum, GravitySolver};
+use super::GRAVITIONAL_CONST;
+
+/// Deeper cells are not split, so coincident bodies can't recurse forever
+const MAX_DEPTH: usize = 32;
+
+/// O(N log N) approximation, a cell far enough away pulls as a single point mass
+pub struct BarnesHut {
+    /// opening angle, a cell of size `s` at distance `d` is approximated when `s / d < theta`
+    pub theta: f64,
+
+    /// Plummer softening length in vpx
+    pub softening: f64,
+
+    nodes: Vec<Node>,
+    /// next body in the same deepest cell, coincident bodies can't be told apart by splitting
+    next: Vec<Option<usize>>,
+}
+
+struct Node {
+    center: DVec2,
+    half_size: f64,
+
+    mass: f64,
+    /// position sum weighted by mass, the center of mass is `weighted / mass`
+    weighted: DVec2,
+
+    /// index of the first of the four consecutive children
+    children: Option<usize>,
+    /// the first body of a leaf, the others of the deepest ones follow [`BarnesHut::next`]
+    body: Option<usize>,
+}
+
+impl Node {
+    fn new(center: DVec2, half_size: f64) -> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            weighted: DVec2::ZERO,
+            children: None,
+            body: None,
+        }
+    }
+
+    fn quadrant(&self, position: DVec2) -> usize {
+        (position.x >= self.center.x) as usize | ((position.y >= self.center.y) as usize) << 1
+    }
+}
+
+impl BarnesHut {
+    pub fn new(theta: f64, softening: f64) -> Self {
+        Self {
+            theta,
+            softening,
+            nodes: Vec::new(),
+            next: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, objects: &[CosmosObject]) {
+        self.nodes.clear();
+        self.next.clear();
+        self.next.resize(objects.len(), None);
+
+        let Some(first) = objects.first() else {
+            return;
+        };
+
+        let (min, max) = objects
+            .iter()
+            .fold((first.position, first.position), |(min, max), object| {
+                (min.min(object.position), max.max(object.position))
+            });
+
+        let half_size = (max - min).max_elem() / 2.0;
+
+        self.nodes
+            .push(Node::new(min.lerp(max, 0.5), half_size.max(f64::EPSILON)));
+
+        for (body, object) in objects.iter().enumerate() {
+            // a single broken body would poison the mass of every cell above it
+            if object.position.is_finite() && object.mass.is_finite() {
+                self.insert(objects, body);
+            }
+        }
+    }
+
+    fn insert(&mut self, objects: &[CosmosObject], body: usize) {
+        let object = &objects[body];
+
+        let mut node = 0;
+
+        for depth in 0.. {
+            let current = &mut self.nodes[node];
+
+            let is_empty_leaf =
+                current.children.is_none() && current.body.is_none() && current.mass == 0.0;
+
+            current.mass += object.mass;
+            current.weighted += object.position * object.mass;
+
+            if is_empty_leaf {
+                current.body = Some(body);
+                return;
+            }
+
+            if depth == MAX_DEPTH {
+                self.next[body] = current.body.replace(body);
+                return;
+            }
+
+            let children = match current.children {
+                Some(children) => children,
+                None => self.split(objects, node),
+            };
+
+            node = children + self.nodes[node].quadrant(object.position);
+        }
+    }
+
+    /// Turns a leaf into an internal node, moving its body into a child
+    fn split(&mut self, objects: &[CosmosObject], node: usize) -> usize {
+        let children = self.nodes.len();
+
+        let Node {
+            center, half_size, ..
+        } = self.nodes[node];
+
+        let quarter = half_size / 2.0;
+
+        for quadrant in 0..4 {
+            let offset = DVec2::new(
+                if quadrant & 1 == 0 { -quarter } else { quarter },
+                if quadrant & 2 == 0 { -quarter } else { quarter },
+            );
+
+            self.nodes.push(Node::new(center + offset, quarter));
+        }
+
+        self.nodes[node].children = Some(children);
+
+        if let Some(body) = self.nodes[node].body.take() {
+            let object = &objects[body];
+            let quadrant = self.nodes[node].quadrant(object.position);
+            let child = &mut self.nodes[children + quadrant];
+
+            child.mass = object.mass;
+            child.weighted = object.position * object.mass;
+            child.body = Some(body);
+        }
+
+        children
+    }
+
+    fn acceleration_of(&self, objects: &[CosmosObject], body: usize) -> DVec2 {
+        let position = objects[body].position;
+
+        let mut acceleration = DVec2::ZERO;
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            if node.mass == 0.0 {
+                continue;
+            }
+
+            let Some(children) = node.children else {
+                // leaves pull body by body, so no body pulls itself
+                let mut other = node.body;
+
+                while let Some(index) = other {
+                    if index != body {
+                        let object = &objects[index];
+
+                        acceleration +=
+                            pull(object.position - position, object.mass, self.softening);
+                    }
+
+                    other = self.next[index];
+                }
+
+                continue;
+            };
+
+            let rect = (node.weighted / node.mass) - position;
+            let dist = rect.length();
+
+            if node.half_size * 2.0 >= self.theta * dist {
+                stack.extend(children..children + 4);
+            } else {
+                acceleration += pull(rect, node.mass, self.softening);
+            }
+        }
+
+        acceleration
+    }
+}
+
+impl GravitySolver for BarnesHut {
+    fn accelerate(&mut self, objects: &mut [CosmosObject]) {
+        self.build(objects);
+
+        for body in 0..objects.len() {
+            objects[body].acceleration = self.acceleration_of(objects, body) * GRAVITIONAL_CONST;
+        }
+    }
+}
+
+/// Relative error of Barnes-Hut accelerations against the direct sum
+#[derive(Clone, Copy, Debug)]
+pub struct AccuracyReport {
+    pub mean: f64,
+    pub max: f64,
+}
+
+/// Compares accelerations computed with opening angle `theta` to the exact ones
+pub fn compare_with_direct_sum(
+    objects: &[CosmosObject],
+    theta: f64,
+    softening: f64,
+) -> AccuracyReport {
+    let mut exact = objects.to_vec();
+    let mut approximate = objects.to_vec();
+
+    DirectSum { softening }.accelerate(&mut exact);
+    BarnesHut::new(theta, softening).accelerate(&mut approximate);
+
+    let errors = exact
+        .iter()
+        .zip(&approximate)
+        .map(|(exact, approximate)| {
+            let error = (approximate.acceleration - exact.acceleration).length();
+            let magnitude = exact.acceleration.length();
+
+            if magnitude > 0.0 {
+                error / magnitude
+            } else {
+                error
+            }
+        })
+        .filter(|error| error.is_finite());
+
+    let (sum, max, count) = errors.fold((0.0, 0.0f64, 0), |(sum, max, count), error| {
+        (sum + error, max.max(error), count + 1)
+    });
+
+    AccuracyReport {
+        mean: if count > 0 { sum / count as f64 } else { 0.0 },
+        max,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A star among scattered bodies, a few of them sharing a point.
+    /// The pseudo random layout is the same on every run
+    fn cluster() -> Vec<CosmosObject> {
+        let mut objects = (0..300)
+            .map(|i| {
+                let i = i as f64;
+
+                CosmosObject {
+                    mass: 1e24 * (1.0 + (i * 0.37).sin().abs()),
+                    position: DVec2::new((i * 12.9898).sin(), (i * 78.233).sin()) * 3000.0,
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        objects.push(CosmosObject {
+            mass: 2e30,
+            ..Default::default()
+        });
+
+        // deeper than `MAX_DEPTH`, these end up in one cell
+        for offset in [0.0, 0.0, 1e-12] {
+            objects.push(CosmosObject {
+                mass: 1e24,
+                position: DVec2::new(100.0 + offset, 200.0),
+                ..Default::default()
+            });
+        }
+
+        objects
+    }
+
+    #[test]
+    fn opening_every_cell_matches_the_direct_sum() {
+        let report = compare_with_direct_sum(&cluster(), 0.0, 0.0);
+
+        assert!(report.max < 1e-9, "{report:?}");
+    }
+
+    #[test]
+    fn error_is_bounded_at_the_default_theta() {
+        let report = compare_with_direct_sum(&cluster(), 0.5, 0.0);
+
+        assert!(report.mean < 1e-3 && report.max < 1e-2, "{report:?}");
+    }
+}
@@ -0,0 +1,362 @@
+use crate::math::DVec2;
+
+use crate::cosmos_object::CosmosObject;
+
+use super::GRAVITIONAL_CONST;
+
+/// Number of fragments a shattered pair breaks into besides the remnant
+const FRAGMENTS: usize = 6;
+
+/// Lightest fragment in kilograms, pairs that would break into lighter ones merge instead,
+/// so fragments don't keep shattering each other
+const MIN_FRAGMENT_MASS: f64 = 1e19;
+
+/// What happens to two overlapping objects
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionResponse {
+    /// objects pass through each other
+    Ignore = 0,
+    /// perfectly inelastic, mass and momentum are conserved
+    Merge = 1,
+    /// perfectly elastic, kinetic energy and momentum are conserved
+    Bounce = 2,
+    /// merge below the escape speed of the pair, shatter above it
+    Fragment = 3,
+}
+
+impl CollisionResponse {
+    pub const ALL: [Self; 4] = [Self::Ignore, Self::Merge, Self::Bounce, Self::Fragment];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ignore => "Ignore",
+            Self::Merge => "Merge",
+            Self::Bounce => "Bounce",
+            Self::Fragment => "Fragment",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionOutcome {
+    Merged,
+    Bounced,
+    Shattered,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CollisionEvent {
+    pub outcome: CollisionOutcome,
+
+    /// point of contact in vpx
+    pub position: DVec2,
+
+    /// masses of the colliding objects in kilograms
+    pub masses: [f64; 2],
+
+    /// simulated seconds since the start
+    pub elapsed: f64,
+}
+
+/// Detects overlapping objects and applies `response` to them
+pub fn resolve(
+    objects: &mut Vec<CosmosObject>,
+    response: CollisionResponse,
+    elapsed: f64,
+    events: &mut Vec<CollisionEvent>,
+) {
+    if response == CollisionResponse::Ignore || objects.len() < 2 {
+        return;
+    }
+
+    let mut alive = vec![true; objects.len()];
+    let mut fragments = Vec::new();
+
+    for (a, b) in overlapping_pairs(objects) {
+        if !alive[a] || !alive[b] {
+            continue;
+        }
+
+        // an earlier collision of this pass may have moved them apart
+        if !overlap(&objects[a], &objects[b]) {
+            continue;
+        }
+
+        let (first, second) = objects.split_at_mut(b);
+        let (a_object, b_object) = (&mut first[a], &mut second[0]);
+
+        let event = CollisionEvent {
+            outcome: CollisionOutcome::Merged,
+            position: contact_point(a_object, b_object),
+            masses: [a_object.mass, b_object.mass],
+            elapsed,
+        };
+
+        let outcome = match response {
+            CollisionResponse::Ignore => unreachable!(),
+            CollisionResponse::Merge => {
+                merge(a_object, b_object);
+                alive[b] = false;
+
+                CollisionOutcome::Merged
+            }
+            CollisionResponse::Bounce => {
+                bounce(a_object, b_object);
+
+                CollisionOutcome::Bounced
+            }
+            CollisionResponse::Fragment => {
+                let shattered = shatter(a_object, b_object, &mut fragments);
+                alive[b] = false;
+
+                if shattered {
+                    CollisionOutcome::Shattered
+                } else {
+                    CollisionOutcome::Merged
+                }
+            }
+        };
+
+        events.push(CollisionEvent { outcome, ..event });
+    }
+
+    let mut alive = alive.into_iter();
+    objects.retain(|_| alive.next().unwrap());
+
+    objects.append(&mut fragments);
+}
+
+fn overlap(a: &CosmosObject, b: &CosmosObject) -> bool {
+    (b.position - a.position).length() < a.radius + b.radius
+}
+
+/// Sweep and prune along the x axis, pairs are ordered as `(a, b)` with `a < b`
+fn overlapping_pairs(objects: &[CosmosObject]) -> Vec<(usize, usize)> {
+    let mut order = (0..objects.len()).collect::<Vec<_>>();
+
+    order.sort_by(|&a, &b| {
+        let a = objects[a].position.x - objects[a].radius;
+        let b = objects[b].position.x - objects[b].radius;
+
+        a.total_cmp(&b)
+    });
+
+    let mut pairs = Vec::new();
+
+    for (i, &a) in order.iter().enumerate() {
+        let right = objects[a].position.x + objects[a].radius;
+
+        for &b in &order[i + 1..] {
+            if objects[b].position.x - objects[b].radius > right {
+                break;
+            }
+
+            if overlap(&objects[a], &objects[b]) {
+                pairs.push((a.min(b), a.max(b)));
+            }
+        }
+    }
+
+    pairs
+}
+
+fn contact_point(a: &CosmosObject, b: &CosmosObject) -> DVec2 {
+    let dist = (b.position - a.position).length();
+
+    if dist > 0.0 {
+        a.position
+            .lerp(b.position, a.radius / (a.radius + b.radius))
+    } else {
+        a.position
+    }
+}
+
+/// Radius of a body holding the volumes of both, so each keeps its density
+fn merged_radius(a: &CosmosObject, b: &CosmosObject) -> f64 {
+    (a.radius.powi(3) + b.radius.powi(3)).cbrt()
+}
+
+/// Merges `b` into `a`, the result looks like the heavier one and takes its id
+fn merge(a: &mut CosmosObject, b: &CosmosObject) {
+    let mass = a.mass + b.mass;
+
+    if b.mass > a.mass {
+        a.id = b.id;
+        a.name.clone_from(&b.name);
+        a.color = b.color;
+    }
+
+    if mass > 0.0 {
+        let center = (a.position * a.mass + b.position * b.mass) / mass;
+
+        a.position = center;
+        a.velocity = (a.velocity * a.mass + b.velocity * b.mass) / mass;
+        a.acceleration = (a.acceleration * a.mass + b.acceleration * b.mass) / mass;
+    }
+
+    a.radius = merged_radius(a, b);
+    a.mass = mass;
+}
+
+fn bounce(a: &mut CosmosObject, b: &mut CosmosObject) {
+    let rect = b.position - a.position;
+    let dist = rect.length();
+
+    let normal = if dist > 0.0 { rect / dist } else { DVec2::X };
+
+    let mass = a.mass + b.mass;
+
+    if mass <= 0.0 {
+        return;
+    }
+
+    // separate the objects so they don't collide again on the next step
+    let overlap = a.radius + b.radius - dist;
+
+    a.position -= normal * overlap * b.mass / mass;
+    b.position += normal * overlap * a.mass / mass;
+
+    let approach = (b.velocity - a.velocity).dot(normal);
+
+    if approach >= 0.0 {
+        return;
+    }
+
+    a.velocity += normal * approach * 2.0 * b.mass / mass;
+    b.velocity -= normal * approach * 2.0 * a.mass / mass;
+}
+
+/// Merges `b` into `a`, then breaks the result into fragments
+/// if the impact speed exceeds the mutual escape speed
+/// and the fragments aren't lighter than [`MIN_FRAGMENT_MASS`].
+/// Returns whether the pair shattered
+fn shatter(a: &mut CosmosObject, b: &CosmosObject, fragments: &mut Vec<CosmosObject>) -> bool {
+    let impact_speed_sq = (b.velocity - a.velocity).length_sq();
+    let escape_speed_sq = 2.0 * GRAVITIONAL_CONST * (a.mass + b.mass) / (a.radius + b.radius);
+
+    merge(a, b);
+
+    if impact_speed_sq <= escape_speed_sq {
+        return false;
+    }
+
+    // the faster the impact, the smaller the remnant
+    let remnant_share = (escape_speed_sq / impact_speed_sq).max(0.1);
+
+    let total_mass = a.mass;
+    let total_radius = a.radius;
+
+    let fragment_mass = total_mass * (1.0 - remnant_share) / FRAGMENTS as f64;
+
+    if fragment_mass < MIN_FRAGMENT_MASS {
+        return false;
+    }
+
+    let fragment_radius = total_radius * (fragment_mass / total_mass).cbrt();
+
+    a.mass = total_mass * remnant_share;
+    a.radius = total_radius * remnant_share.cbrt();
+
+    let distance = a.radius + fragment_radius * 2.0;
+    let speed = (impact_speed_sq - escape_speed_sq).sqrt();
+
+    // fragments of equal mass on a ring fly away symmetrically, so the momentum is kept
+    for i in 0..FRAGMENTS {
+        let dir = DVec2::angled(std::f64::consts::TAU * i as f64 / FRAGMENTS as f64);
+
+        fragments.push(CosmosObject {
+            id: 0,
+            name: None,
+            color: a.color,
+            mass: fragment_mass,
+            radius: fragment_radius,
+            position: a.position + dir * distance,
+            velocity: a.velocity + dir * speed,
+            acceleration: a.acceleration,
+        });
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(mass: f64, position: DVec2, velocity: DVec2) -> CosmosObject {
+        CosmosObject {
+            mass,
+            radius: 1.0,
+            position,
+            velocity,
+            ..Default::default()
+        }
+    }
+
+    fn momentum(objects: &[&CosmosObject]) -> DVec2 {
+        objects.iter().fold(DVec2::ZERO, |sum, object| {
+            sum + object.velocity * object.mass
+        })
+    }
+
+    fn kinetic_energy(objects: &[&CosmosObject]) -> f64 {
+        objects
+            .iter()
+            .map(|object| 0.5 * object.mass * object.velocity.length_sq())
+            .sum()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-12 * a.abs().max(b.abs()), "{a} != {b}");
+    }
+
+    #[test]
+    fn merge_conserves_mass_and_momentum() {
+        let mut a = body(3e24, DVec2::new(0.0, 0.0), DVec2::new(2.0, -1.0));
+        let b = body(5e24, DVec2::new(1.5, 0.5), DVec2::new(-4.0, 3.0));
+
+        let mass = a.mass + b.mass;
+        let before = momentum(&[&a, &b]);
+
+        merge(&mut a, &b);
+        let after = momentum(&[&a]);
+
+        assert_close(a.mass, mass);
+        assert_close(after.x, before.x);
+        assert_close(after.y, before.y);
+    }
+
+    #[test]
+    fn bounce_conserves_kinetic_energy_and_momentum() {
+        let mut a = body(3e24, DVec2::new(0.0, 0.0), DVec2::new(2.0, -1.0));
+        let mut b = body(5e24, DVec2::new(1.5, 0.5), DVec2::new(-4.0, 3.0));
+
+        let energy = kinetic_energy(&[&a, &b]);
+        let before = momentum(&[&a, &b]);
+
+        bounce(&mut a, &mut b);
+        let after = momentum(&[&a, &b]);
+
+        assert_close(kinetic_energy(&[&a, &b]), energy);
+        assert_close(after.x, before.x);
+        assert_close(after.y, before.y);
+        assert!((b.velocity - a.velocity).dot(b.position - a.position) >= 0.0);
+    }
+
+    #[test]
+    fn light_pairs_merge_instead_of_shattering() {
+        let mut fragments = Vec::new();
+
+        let mut a = body(1e19, DVec2::new(0.0, 0.0), DVec2::new(1e4, 0.0));
+        let b = body(1e19, DVec2::new(1.5, 0.0), DVec2::new(-1e4, 0.0));
+
+        assert!(!shatter(&mut a, &b, &mut fragments));
+        assert!(fragments.is_empty());
+
+        let mut a = body(1e24, DVec2::new(0.0, 0.0), DVec2::new(1e4, 0.0));
+        let b = body(1e24, DVec2::new(1.5, 0.0), DVec2::new(-1e4, 0.0));
+
+        assert!(shatter(&mut a, &b, &mut fragments));
+        assert_eq!(fragments.len(), FRAGMENTS);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::cosmos_object::CosmosObject;
+use crate::math::DVec2;
+
+use super::GRAVITIONAL_CONST;
+
+/// Quantities conserved by an isolated system, in kg and vpx.
+///
+/// Only numerical error and inelastic collisions change them
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Diagnostics {
+    pub kinetic_energy: f64,
+
+    /// with the same Plummer softening as the gravity solver
+    pub potential_energy: f64,
+
+    pub momentum: DVec2,
+
+    /// about the origin
+    pub angular_momentum: f64,
+
+    pub mass: f64,
+    pub center_of_mass: DVec2,
+
+    /// sum of the momentum magnitudes, the scale of the momentum drift
+    /// when the total momentum is close to zero
+    momentum_scale: f64,
+
+    /// sum of the angular momentum magnitudes, same as `momentum_scale`
+    angular_momentum_scale: f64,
+}
+
+/// Relative change of the conserved quantities since a reference [`Diagnostics`]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Drift {
+    pub energy: f64,
+    pub momentum: f64,
+    pub angular_momentum: f64,
+}
+
+impl Diagnostics {
+    /// Measures `objects`, the potential energy takes O(N²)
+    pub fn measure(objects: &[CosmosObject], softening: f64) -> Self {
+        let mut diagnostics = Self::default();
+        let mut weighted = DVec2::ZERO;
+
+        for (i, a) in objects.iter().enumerate() {
+            let momentum = a.velocity * a.mass;
+            let angular_momentum = a.position.cross(momentum);
+
+            diagnostics.kinetic_energy += 0.5 * a.mass * a.velocity.length_sq();
+            diagnostics.momentum += momentum;
+            diagnostics.angular_momentum += angular_momentum;
+            diagnostics.mass += a.mass;
+            diagnostics.momentum_scale += momentum.length();
+            diagnostics.angular_momentum_scale += angular_momentum.abs();
+
+            weighted += a.position * a.mass;
+
+            for b in &objects[i + 1..] {
+                let dist_sq = (b.position - a.position).length_sq() + softening * softening;
+
+                if dist_sq > 0.0 {
+                    diagnostics.potential_energy -= a.mass * b.mass / dist_sq.sqrt();
+                }
+            }
+        }
+
+        diagnostics.potential_energy *= GRAVITIONAL_CONST;
+
+        if diagnostics.mass > 0.0 {
+            diagnostics.center_of_mass = weighted / diagnostics.mass;
+        }
+
+        diagnostics
+    }
+
+    pub fn energy(&self) -> f64 {
+        self.kinetic_energy + self.potential_energy
+    }
+
+    pub fn center_of_mass_velocity(&self) -> DVec2 {
+        if self.mass > 0.0 {
+            self.momentum / self.mass
+        } else {
+            DVec2::ZERO
+        }
+    }
+
+    /// Change since `initial` relative to its magnitude
+    pub fn drift(&self, initial: &Self) -> Drift {
+        Drift {
+            energy: relative(self.energy() - initial.energy(), initial.energy().abs()),
+            momentum: relative(
+                (self.momentum - initial.momentum).length(),
+                initial.momentum_scale,
+            ),
+            angular_momentum: relative(
+                (self.angular_momentum - initial.angular_momentum).abs(),
+                initial.angular_momentum_scale,
+            ),
+        }
+    }
+}
+
+fn relative(change: f64, scale: f64) -> f64 {
+    if scale > 0.0 {
+        change / scale
+    } else {
+        0.0
+    }
+}
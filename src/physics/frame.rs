@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+
+use crate::cosmos_object::{BodyPatch, CosmosObject};
+use crate::recorder::Recorder;
+
+use super::{Diagnostics, Drift, History};
+
+/// Complete and immutable state of the system after a batch of steps
+#[derive(Clone, Default)]
+pub struct Frame {
+    pub objects: Vec<CosmosObject>,
+
+    /// simulated seconds since the start
+    pub elapsed: f64,
+
+    /// measured every few frames and only while [`SimulationState::measures_diagnostics`],
+    /// the last measurement otherwise
+    ///
+    /// [`SimulationState::measures_diagnostics`]: super::SimulationState::measures_diagnostics
+    pub diagnostics: Diagnostics,
+    /// elapsed time of the measurement in `diagnostics`,
+    /// tells the frames with a fresh measurement apart
+    pub measured: f64,
+
+    /// since the start or the first measurement after the last edit of the system
+    pub drift: Drift,
+}
+
+/// Change of the system requested from outside of the physics thread
+pub enum Edit {
+    Add(CosmosObject),
+    /// changes the object with the given id, if it still exists
+    Update(u64, BodyPatch),
+    /// removes the object with the given id
+    Remove(u64),
+    /// restores the recorded frame closest to the given elapsed time
+    Rewind(f64),
+}
+
+/// Exchange between the physics thread and its readers.
+///
+/// The physics thread owns the objects and publishes a new [`Frame`] after every batch,
+/// readers always get the latest complete one without ever blocking the integrator
+pub struct FrameBuffer {
+    latest: ArcSwap<Frame>,
+
+    /// edits not yet applied by the physics thread
+    edits: Mutex<Vec<Edit>>,
+
+    history: Mutex<History>,
+
+    recorder: Mutex<Option<Recorder>>,
+
+    /// id given to the next new object
+    next_id: AtomicU64,
+}
+
+impl FrameBuffer {
+    /// Objects without an id get one
+    pub fn new(mut objects: Vec<CosmosObject>) -> Self {
+        let mut last_id = objects.iter().map(|object| object.id).max().unwrap_or(0);
+
+        for object in &mut objects {
+            if object.id == 0 {
+                last_id += 1;
+                object.id = last_id;
+            }
+        }
+
+        let first = Arc::new(Frame {
+            objects,
+            ..Default::default()
+        });
+
+        // a frame every 6 simulated hours
+        let mut history = History::new(60. * 60. * 6., 2000);
+        history.record(&first);
+
+        Self {
+            latest: ArcSwap::new(first),
+            edits: Mutex::new(Vec::new()),
+            history: Mutex::new(history),
+            recorder: Mutex::new(None),
+            next_id: AtomicU64::new(last_id + 1),
+        }
+    }
+
+    pub fn latest(&self) -> Arc<Frame> {
+        self.latest.load_full()
+    }
+
+    /// Makes `frame` the latest one, `record` also adds it to the history and the recording
+    pub fn publish(&self, frame: Frame, record: bool) {
+        let frame = Arc::new(frame);
+
+        if record {
+            self.history.lock().unwrap().record(&frame);
+
+            if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+                recorder.record(&frame);
+            }
+        }
+
+        self.latest.store(frame);
+    }
+
+    pub fn history_at(&self, elapsed: f64) -> Option<Arc<Frame>> {
+        self.history.lock().unwrap().at(elapsed)
+    }
+
+    /// Elapsed time of the first and the last recorded frames
+    pub fn history_span(&self) -> Option<(f64, f64)> {
+        self.history.lock().unwrap().span()
+    }
+
+    /// Starts sampling the published frames with `recorder`, beginning with the latest one.
+    /// Replaces the previous recording if there is one
+    pub fn start_recording(&self, mut recorder: Recorder) {
+        recorder.record(&self.latest());
+
+        *self.recorder.lock().unwrap() = Some(recorder);
+    }
+
+    pub fn stop_recording(&self) -> Option<Recorder> {
+        self.recorder.lock().unwrap().take()
+    }
+
+    /// Number of samples in the current recording
+    pub fn recorded_samples(&self) -> Option<usize> {
+        self.recorder
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(Recorder::samples)
+    }
+
+    /// Unused object id, so readers can refer to the objects they add
+    pub fn new_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Queues `edit` to be applied before the next step
+    pub fn edit(&self, edit: Edit) {
+        self.edits.lock().unwrap().push(edit);
+    }
+
+    pub fn take_edits(&self) -> Vec<Edit> {
+        std::mem::take(&mut *self.edits.lock().unwrap())
+    }
+}
@@ -1,11 +1,87 @@
+use crate::math::DVec2;
+
 use crate::cosmos_object::CosmosObject;
 
+use super::barnes_hut::BarnesHut;
+use super::GRAVITIONAL_CONST;
+
+/// Computes the gravitational acceleration of every object
+pub trait GravitySolver: Send {
+    /// Recomputes `acceleration` of every object from the current positions
+    fn accelerate(&mut self, objects: &mut [CosmosObject]);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SolverKind {
+    DirectSum = 0,
+    BarnesHut = 1,
+}
+
+impl SolverKind {
+    pub const ALL: [Self; 2] = [Self::DirectSum, Self::BarnesHut];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DirectSum => "Direct sum",
+            Self::BarnesHut => "Barnes-Hut",
+        }
+    }
+
+    /// `theta` is the opening angle, only used by [`SolverKind::BarnesHut`]
+    pub fn create(self, theta: f64, softening: f64) -> Box<dyn GravitySolver> {
+        match self {
+            Self::DirectSum => Box::new(DirectSum { softening }),
+            Self::BarnesHut => Box::new(BarnesHut::new(theta, softening)),
+        }
+    }
+}
+
+/// Exact O(N²) sum over all pairs
+pub struct DirectSum {
+    /// Plummer softening length in vpx
+    pub softening: f64,
+}
+
+impl GravitySolver for DirectSum {
+    fn accelerate(&mut self, objects: &mut [CosmosObject]) {
+        for i in 0..objects.len() {
+            let mut acceleration = DVec2::ZERO;
+
+            for j in 0..objects.len() {
+                if i == j {
+                    continue;
+                }
+
+                acceleration += gravity(&objects[i], &objects[j], self.softening);
+            }
+
+            objects[i].acceleration = acceleration * GRAVITIONAL_CONST;
+        }
+    }
+}
+
 #[inline(always)]
-pub fn gravity(a: &mut CosmosObject, b: &CosmosObject) {
-    let rect = b.position - a.position;
-    let dist = rect.length();
+pub fn gravity(a: &CosmosObject, b: &CosmosObject, softening: f64) -> DVec2 {
+    pull(b.position - a.position, b.mass, softening)
+}
+
+/// Acceleration towards a point mass at `rect` divided by the gravitational constant.
+///
+/// The Plummer `softening` length keeps the pull finite at small distances,
+/// coincident points and non-finite input don't pull at all
+#[inline(always)]
+pub fn pull(rect: DVec2, mass: f64, softening: f64) -> DVec2 {
+    let dist_sq = rect.length_sq() + softening * softening;
+
+    if dist_sq == 0.0 {
+        return DVec2::ZERO;
+    }
 
-    let dir = rect.normalized();
+    let pull = rect * mass / (dist_sq * dist_sq.sqrt());
 
-    a.acceleration += dir * b.mass / dist.powi(2);
+    if pull.is_finite() {
+        pull
+    } else {
+        DVec2::ZERO
+    }
 }
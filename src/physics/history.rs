@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use super::Frame;
+
+/// Bounded record of past frames, sampled once per `interval` of simulated time
+pub struct History {
+    frames: VecDeque<Arc<Frame>>,
+
+    /// simulated seconds between two recorded frames
+    pub interval: f64,
+
+    /// maximum number of recorded frames
+    pub capacity: usize,
+
+    /// maximum number of objects in all recorded frames together,
+    /// keeps the memory bounded for large systems
+    pub max_objects: usize,
+
+    /// number of objects in all recorded frames together
+    objects: usize,
+}
+
+impl History {
+    pub const fn new(interval: f64, capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            interval,
+            capacity,
+            max_objects: 1_000_000,
+            objects: 0,
+        }
+    }
+
+    /// Records `frame` if it's at least `interval` apart from the last recorded one.
+    /// Frames later than `frame` belong to a discarded future and are dropped
+    pub fn record(&mut self, frame: &Arc<Frame>) {
+        while let Some(last) = self.frames.back() {
+            if last.elapsed <= frame.elapsed {
+                break;
+            }
+
+            self.objects -= last.objects.len();
+            self.frames.pop_back();
+        }
+
+        if let Some(last) = self.frames.back() {
+            if frame.elapsed - last.elapsed < self.interval {
+                return;
+            }
+        }
+
+        self.objects += frame.objects.len();
+        self.frames.push_back(Arc::clone(frame));
+
+        while self.frames.len() > self.capacity
+            || (self.objects > self.max_objects && self.frames.len() > 1)
+        {
+            let first = self.frames.pop_front().unwrap();
+            self.objects -= first.objects.len();
+        }
+    }
+
+    /// The latest recorded frame not later than `elapsed`, the earliest one if all are later
+    pub fn at(&self, elapsed: f64) -> Option<Arc<Frame>> {
+        let index = self
+            .frames
+            .partition_point(|frame| frame.elapsed <= elapsed)
+            .saturating_sub(1);
+
+        self.frames.get(index).cloned()
+    }
+
+    /// Elapsed time of the first and the last recorded frames
+    pub fn span(&self) -> Option<(f64, f64)> {
+        Some((self.frames.front()?.elapsed, self.frames.back()?.elapsed))
+    }
+}
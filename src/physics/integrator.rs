@@ -0,0 +1,201 @@
+use crate::math::DVec2;
+
+use crate::cosmos_object::CosmosObject;
+
+use super::gravity::GravitySolver;
+
+/// Advances positions and velocities of all objects by one time step
+pub trait Integrator: Send {
+    fn step(
+        &mut self,
+        objects: &mut [CosmosObject],
+        delta_time: f64,
+        solver: &mut dyn GravitySolver,
+    );
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IntegratorKind {
+    SemiImplicitEuler = 0,
+    Leapfrog = 1,
+    VelocityVerlet = 2,
+    RungeKutta4 = 3,
+}
+
+impl IntegratorKind {
+    pub const ALL: [Self; 4] = [
+        Self::SemiImplicitEuler,
+        Self::Leapfrog,
+        Self::VelocityVerlet,
+        Self::RungeKutta4,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SemiImplicitEuler => "Semi-implicit Euler",
+            Self::Leapfrog => "Leapfrog",
+            Self::VelocityVerlet => "Velocity Verlet",
+            Self::RungeKutta4 => "Runge-Kutta 4",
+        }
+    }
+
+    /// Whether stepping back by `-dt` retraces a step of `dt`, so the time can run backwards
+    pub fn is_time_reversible(self) -> bool {
+        match self {
+            Self::SemiImplicitEuler | Self::RungeKutta4 => false,
+            Self::Leapfrog | Self::VelocityVerlet => true,
+        }
+    }
+
+    pub fn create(self) -> Box<dyn Integrator> {
+        match self {
+            Self::SemiImplicitEuler => Box::new(SemiImplicitEuler),
+            Self::Leapfrog => Box::new(Leapfrog),
+            Self::VelocityVerlet => Box::<VelocityVerlet>::default(),
+            Self::RungeKutta4 => Box::<RungeKutta4>::default(),
+        }
+    }
+}
+
+/// First order, the original integrator of the simulation
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(
+        &mut self,
+        objects: &mut [CosmosObject],
+        delta_time: f64,
+        solver: &mut dyn GravitySolver,
+    ) {
+        solver.accelerate(objects);
+
+        for object in objects.iter_mut() {
+            object.velocity += object.acceleration * delta_time;
+            object.position += object.velocity * delta_time;
+        }
+    }
+}
+
+/// Symplectic drift-kick-drift scheme, one force evaluation per step
+pub struct Leapfrog;
+
+impl Integrator for Leapfrog {
+    fn step(
+        &mut self,
+        objects: &mut [CosmosObject],
+        delta_time: f64,
+        solver: &mut dyn GravitySolver,
+    ) {
+        let half_dt = delta_time / 2.0;
+
+        for object in objects.iter_mut() {
+            object.position += object.velocity * half_dt;
+        }
+
+        solver.accelerate(objects);
+
+        for object in objects.iter_mut() {
+            object.velocity += object.acceleration * delta_time;
+            object.position += object.velocity * half_dt;
+        }
+    }
+}
+
+/// Symplectic kick-drift-kick scheme, reuses the acceleration of the previous step.
+///
+/// It has to be created again whenever the objects change outside of its steps
+#[derive(Default)]
+pub struct VelocityVerlet {
+    /// whether the stored accelerations are those of the current positions
+    primed: bool,
+}
+
+impl Integrator for VelocityVerlet {
+    fn step(
+        &mut self,
+        objects: &mut [CosmosObject],
+        delta_time: f64,
+        solver: &mut dyn GravitySolver,
+    ) {
+        if !self.primed {
+            solver.accelerate(objects);
+        }
+
+        let half_dt = delta_time / 2.0;
+
+        for object in objects.iter_mut() {
+            object.velocity += object.acceleration * half_dt;
+            object.position += object.velocity * delta_time;
+        }
+
+        solver.accelerate(objects);
+
+        for object in objects.iter_mut() {
+            object.velocity += object.acceleration * half_dt;
+        }
+
+        self.primed = true;
+    }
+}
+
+/// Classical fourth order Runge-Kutta, four force evaluations per step.
+///
+/// The acceleration left on the objects is the weighted mean of the stages,
+/// a fifth evaluation at the final positions would only serve the display
+#[derive(Default)]
+pub struct RungeKutta4 {
+    probe: Vec<CosmosObject>,
+    /// `(dx, dv)` slopes of the current stage
+    slopes: Vec<(DVec2, DVec2)>,
+    /// weighted sum of the slopes of all stages
+    sum: Vec<(DVec2, DVec2)>,
+}
+
+impl Integrator for RungeKutta4 {
+    fn step(
+        &mut self,
+        objects: &mut [CosmosObject],
+        delta_time: f64,
+        solver: &mut dyn GravitySolver,
+    ) {
+        self.probe.clear();
+        self.probe.extend_from_slice(objects);
+
+        self.sum.clear();
+        self.sum.resize(objects.len(), (DVec2::ZERO, DVec2::ZERO));
+
+        const STAGES: [(f64, f64); 4] = [(0.0, 1.0), (0.5, 2.0), (0.5, 2.0), (1.0, 1.0)];
+
+        for (offset, weight) in STAGES {
+            // the probe state of this stage is offset along the slopes of the previous one
+            if offset != 0.0 {
+                for ((probe, object), &(dx, dv)) in
+                    self.probe.iter_mut().zip(objects.iter()).zip(&self.slopes)
+                {
+                    probe.position = object.position + dx * delta_time * offset;
+                    probe.velocity = object.velocity + dv * delta_time * offset;
+                }
+            }
+
+            solver.accelerate(&mut self.probe);
+
+            self.slopes.clear();
+            self.slopes.extend(
+                self.probe
+                    .iter()
+                    .map(|probe| (probe.velocity, probe.acceleration)),
+            );
+
+            for (sum, &(dx, dv)) in self.sum.iter_mut().zip(&self.slopes) {
+                sum.0 += dx * weight;
+                sum.1 += dv * weight;
+            }
+        }
+
+        for (object, &(dx, dv)) in objects.iter_mut().zip(&self.sum) {
+            object.position += dx * delta_time / 6.0;
+            object.velocity += dv * delta_time / 6.0;
+            object.acceleration = dv / 6.0;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::cosmos_object::CosmosObject;
+use crate::math::DVec2;
+
+use super::{IntegratorKind, SimulationState, SolverKind, StepController};
+
+/// Forward integration of a copy of the system, leaving the simulation untouched
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Prediction {
+    /// simulated seconds to look ahead
+    pub horizon: f64,
+
+    pub integrator: IntegratorKind,
+    pub solver: SolverKind,
+    pub theta: f64,
+    pub softening: f64,
+
+    /// fraction of the encounter time scale a step may cover, see [`StepController`]
+    pub accuracy: f64,
+
+    /// points of the trajectory at most, also bounds the length of a step
+    pub points: usize,
+}
+
+/// Predicted path of a body
+#[derive(Clone, Default, Debug)]
+pub struct Trajectory {
+    /// positions in vpx, starting at the current one
+    pub points: Vec<DVec2>,
+
+    /// whether the path ends by touching another body
+    pub collides: bool,
+}
+
+impl Prediction {
+    /// Steps taken at most, the trajectory is cut short when they run out
+    const MAX_STEPS: usize = 20_000;
+
+    /// Pairs of bodies visited at most over all steps, fewer steps are taken in large systems
+    /// so a prediction takes a few milliseconds and fits into a frame
+    const MAX_PAIRS: usize = 100_000;
+
+    /// Same integrator, gravity and accuracy as the simulation
+    pub fn new(sim_state: &SimulationState, horizon: f64) -> Self {
+        Self {
+            horizon,
+            integrator: sim_state.integrator(),
+            solver: sim_state.solver(),
+            theta: sim_state.theta(),
+            softening: sim_state.softening(),
+            accuracy: sim_state.step_accuracy(),
+            points: 500,
+        }
+    }
+
+    /// Path of the body at `index` over the horizon, the other bodies move along
+    /// but don't collide with each other
+    pub fn trajectory(&self, mut objects: Vec<CosmosObject>, index: usize) -> Trajectory {
+        let mut trajectory = Trajectory {
+            points: vec![objects[index].position],
+            collides: false,
+        };
+
+        if self.horizon <= 0.0 || self.points == 0 {
+            return trajectory;
+        }
+
+        let spacing = self.horizon / self.points as f64;
+
+        let mut integrator = self.integrator.create();
+        let mut solver = self.solver.create(self.theta, self.softening);
+        let controller = StepController::new(self.accuracy, spacing);
+
+        let mut elapsed = 0.0;
+        let mut step = 0.0;
+        let mut next_point = spacing;
+
+        let pairs = objects.len() * objects.len();
+        let max_steps = (Self::MAX_PAIRS / pairs).clamp(1, Self::MAX_STEPS);
+
+        for _ in 0..max_steps {
+            step = controller.step(&objects, step).min(self.horizon - elapsed);
+
+            integrator.step(&mut objects, step, solver.as_mut());
+            elapsed += step;
+
+            let body = &objects[index];
+
+            if !body.is_finite() {
+                break;
+            }
+
+            trajectory.collides = touches_other(&objects, index);
+
+            if trajectory.collides || elapsed >= next_point || elapsed >= self.horizon {
+                trajectory.points.push(body.position);
+                next_point = elapsed + spacing;
+            }
+
+            if trajectory.collides || elapsed >= self.horizon {
+                break;
+            }
+        }
+
+        trajectory
+    }
+}
+
+fn touches_other(objects: &[CosmosObject], index: usize) -> bool {
+    let body = &objects[index];
+
+    objects.iter().enumerate().any(|(other, object)| {
+        other != index && (object.position - body.position).length() <= object.radius + body.radius
+    })
+}
@@ -1,35 +1,127 @@
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread::Thread;
 
-use atomic_float::AtomicF32;
+use atomic_float::AtomicF64;
+
+use super::{CollisionEvent, CollisionResponse, IntegratorKind, SolverKind, StepMode};
 
 pub struct SimulationState {
-    delta_time: AtomicF32,
-    time_speed: AtomicF32,
-    elapsed: AtomicF32,
+    delta_time: AtomicF64,
+    time_speed: AtomicF64,
+    elapsed: AtomicF64,
+    integrator: AtomicU8,
+    step_mode: AtomicU8,
+    /// step of the fixed mode, upper bound of the step in the adaptive mode
+    fixed_step: AtomicF64,
+    /// fraction of the encounter time scale an adaptive step may cover
+    step_accuracy: AtomicF64,
+    solver: AtomicU8,
+    /// opening angle of the Barnes-Hut solver
+    theta: AtomicF64,
+    /// Plummer softening length in vpx
+    softening: AtomicF64,
+    collision_response: AtomicU8,
+    /// collisions not yet taken by the UI
+    collisions: Mutex<Vec<CollisionEvent>>,
+    paused: AtomicBool,
+    /// steps requested while paused and not yet taken
+    pending_steps: AtomicUsize,
+    /// parks itself while paused
+    physics_thread: OnceLock<Thread>,
+    /// target iterations of the physics loop per second, non-positive to run at max speed
+    tick_rate: AtomicF64,
+    measured_tick_rate: AtomicF64,
+    /// share of the wall clock time the physics thread is busy
+    load: AtomicF64,
+    /// whether the published frames carry fresh diagnostics, measuring them takes O(N²)
+    diagnostics: AtomicBool,
 }
 
 impl SimulationState {
-    pub const fn new(time_speed: f32) -> Self {
+    pub const fn new(time_speed: f64) -> Self {
         Self {
-            delta_time: AtomicF32::new(0.0),
-            elapsed: AtomicF32::new(0.0),
-            time_speed: AtomicF32::new(time_speed),
+            delta_time: AtomicF64::new(0.0),
+            elapsed: AtomicF64::new(0.0),
+            time_speed: AtomicF64::new(time_speed),
+            integrator: AtomicU8::new(IntegratorKind::SemiImplicitEuler as u8),
+            step_mode: AtomicU8::new(StepMode::WallClock as u8),
+            fixed_step: AtomicF64::new(60.0),
+            step_accuracy: AtomicF64::new(0.01),
+            solver: AtomicU8::new(SolverKind::DirectSum as u8),
+            theta: AtomicF64::new(0.5),
+            softening: AtomicF64::new(0.0),
+            collision_response: AtomicU8::new(CollisionResponse::Ignore as u8),
+            collisions: Mutex::new(Vec::new()),
+            paused: AtomicBool::new(false),
+            pending_steps: AtomicUsize::new(0),
+            physics_thread: OnceLock::new(),
+            tick_rate: AtomicF64::new(0.0),
+            measured_tick_rate: AtomicF64::new(0.0),
+            load: AtomicF64::new(0.0),
+            diagnostics: AtomicBool::new(false),
         }
     }
 
-    pub fn delta_time(&self) -> f32 {
+    pub const fn with_integrator(mut self, integrator: IntegratorKind) -> Self {
+        self.integrator = AtomicU8::new(integrator as u8);
+
+        self
+    }
+
+    pub const fn with_step_mode(mut self, step_mode: StepMode, fixed_step: f64) -> Self {
+        self.step_mode = AtomicU8::new(step_mode as u8);
+        self.fixed_step = AtomicF64::new(fixed_step);
+
+        self
+    }
+
+    pub const fn with_solver(mut self, solver: SolverKind, theta: f64) -> Self {
+        self.solver = AtomicU8::new(solver as u8);
+        self.theta = AtomicF64::new(theta);
+
+        self
+    }
+
+    pub const fn with_collision_response(mut self, collision_response: CollisionResponse) -> Self {
+        self.collision_response = AtomicU8::new(collision_response as u8);
+
+        self
+    }
+
+    pub const fn with_tick_rate(mut self, tick_rate: f64) -> Self {
+        self.tick_rate = AtomicF64::new(tick_rate);
+
+        self
+    }
+
+    /// Length of the last step taken
+    pub fn delta_time(&self) -> f64 {
         self.delta_time.load(Ordering::Relaxed)
     }
 
-    pub fn set_delta_time(&self, delta_time: f32) {
+    pub fn set_delta_time(&self, delta_time: f64) {
         self.delta_time.store(delta_time, Ordering::Relaxed)
     }
 
-    pub fn time_speed(&self) -> f32 {
+    pub fn time_speed(&self) -> f64 {
         self.time_speed.load(Ordering::Relaxed)
     }
 
-    pub fn zoom_time_speed(&self, zoom_delta: f32) {
+    pub fn set_time_speed(&self, time_speed: f64) {
+        self.time_speed.store(time_speed, Ordering::Relaxed)
+    }
+
+    /// Runs the time the other way, see [`IntegratorKind::is_time_reversible`]
+    pub fn reverse_time(&self) {
+        self.time_speed
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |time_speed| {
+                Some(-time_speed)
+            })
+            .unwrap();
+    }
+
+    pub fn zoom_time_speed(&self, zoom_delta: f64) {
         self.time_speed
             .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |time_speed| {
                 Some(time_speed * zoom_delta)
@@ -37,11 +129,154 @@ impl SimulationState {
             .unwrap();
     }
 
-    pub fn elapsed(&self) -> f32 {
+    pub fn elapsed(&self) -> f64 {
         self.elapsed.load(Ordering::Relaxed)
     }
 
-    pub fn update_elapsed(&self, delta_time: f32) {
+    pub fn update_elapsed(&self, delta_time: f64) {
         self.elapsed.fetch_add(delta_time, Ordering::Relaxed);
     }
+
+    pub fn set_elapsed(&self, elapsed: f64) {
+        self.elapsed.store(elapsed, Ordering::Relaxed);
+    }
+
+    pub fn integrator(&self) -> IntegratorKind {
+        IntegratorKind::ALL[self.integrator.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_integrator(&self, integrator: IntegratorKind) {
+        self.integrator.store(integrator as u8, Ordering::Relaxed)
+    }
+
+    pub fn step_mode(&self) -> StepMode {
+        StepMode::ALL[self.step_mode.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_step_mode(&self, step_mode: StepMode) {
+        self.step_mode.store(step_mode as u8, Ordering::Relaxed)
+    }
+
+    pub fn fixed_step(&self) -> f64 {
+        self.fixed_step.load(Ordering::Relaxed)
+    }
+
+    pub fn set_fixed_step(&self, fixed_step: f64) {
+        self.fixed_step.store(fixed_step, Ordering::Relaxed)
+    }
+
+    pub fn step_accuracy(&self) -> f64 {
+        self.step_accuracy.load(Ordering::Relaxed)
+    }
+
+    pub fn set_step_accuracy(&self, step_accuracy: f64) {
+        self.step_accuracy.store(step_accuracy, Ordering::Relaxed)
+    }
+
+    pub fn solver(&self) -> SolverKind {
+        SolverKind::ALL[self.solver.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_solver(&self, solver: SolverKind) {
+        self.solver.store(solver as u8, Ordering::Relaxed)
+    }
+
+    pub fn theta(&self) -> f64 {
+        self.theta.load(Ordering::Relaxed)
+    }
+
+    pub fn set_theta(&self, theta: f64) {
+        self.theta.store(theta, Ordering::Relaxed)
+    }
+
+    pub fn softening(&self) -> f64 {
+        self.softening.load(Ordering::Relaxed)
+    }
+
+    pub fn set_softening(&self, softening: f64) {
+        self.softening.store(softening, Ordering::Relaxed)
+    }
+
+    pub fn collision_response(&self) -> CollisionResponse {
+        CollisionResponse::ALL[self.collision_response.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_collision_response(&self, collision_response: CollisionResponse) {
+        self.collision_response
+            .store(collision_response as u8, Ordering::Relaxed)
+    }
+
+    pub fn push_collisions(&self, collisions: impl IntoIterator<Item = CollisionEvent>) {
+        self.collisions.lock().unwrap().extend(collisions);
+    }
+
+    /// Collisions happened since the last call
+    pub fn take_collisions(&self) -> Vec<CollisionEvent> {
+        std::mem::take(&mut *self.collisions.lock().unwrap())
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        self.wake();
+    }
+
+    pub fn measures_diagnostics(&self) -> bool {
+        self.diagnostics.load(Ordering::Relaxed)
+    }
+
+    pub fn set_measure_diagnostics(&self, measure: bool) {
+        self.diagnostics.store(measure, Ordering::Relaxed);
+    }
+
+    /// Asks a paused simulation to take `steps` more steps
+    pub fn request_steps(&self, steps: usize) {
+        self.pending_steps.fetch_add(steps, Ordering::Relaxed);
+        self.wake();
+    }
+
+    pub fn take_pending_steps(&self) -> usize {
+        self.pending_steps.swap(0, Ordering::Relaxed)
+    }
+
+    /// Registers the calling thread as the one [`SimulationState::wake`] unparks
+    pub fn set_physics_thread(&self) {
+        let _ = self.physics_thread.set(std::thread::current());
+    }
+
+    /// Unparks the physics thread, so it notices changes made while it was paused
+    pub fn wake(&self) {
+        if let Some(thread) = self.physics_thread.get() {
+            thread.unpark();
+        }
+    }
+
+    /// Target iterations of the physics loop per second, `None` to run at max speed
+    pub fn tick_rate(&self) -> Option<f64> {
+        let tick_rate = self.tick_rate.load(Ordering::Relaxed);
+
+        (tick_rate > 0.0).then_some(tick_rate)
+    }
+
+    pub fn set_tick_rate(&self, tick_rate: Option<f64>) {
+        self.tick_rate
+            .store(tick_rate.unwrap_or(0.0), Ordering::Relaxed)
+    }
+
+    pub fn measured_tick_rate(&self) -> f64 {
+        self.measured_tick_rate.load(Ordering::Relaxed)
+    }
+
+    pub fn load(&self) -> f64 {
+        self.load.load(Ordering::Relaxed)
+    }
+
+    pub fn set_tick_stats(&self, measured_tick_rate: f64, load: f64) {
+        self.measured_tick_rate
+            .store(measured_tick_rate, Ordering::Relaxed);
+        self.load.store(load, Ordering::Relaxed);
+    }
 }
@@ -0,0 +1,148 @@
+use std::ops::RangeInclusive;
+
+use crate::cosmos_object::CosmosObject;
+
+use super::GRAVITIONAL_CONST;
+
+/// How the length of a physics step is chosen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepMode {
+    /// one step per iteration covering the wall clock time it took
+    WallClock = 0,
+    /// constant step, reproducible
+    Fixed = 1,
+    /// step shrinks during close encounters, see [`StepController`]
+    Adaptive = 2,
+}
+
+impl StepMode {
+    pub const ALL: [Self; 3] = [Self::WallClock, Self::Fixed, Self::Adaptive];
+
+    /// Accepted sizes of the fixed step and of the largest adaptive one, in seconds
+    pub const STEP_RANGE: RangeInclusive<f64> = 1e-3..=86_400.0;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::WallClock => "Wall clock",
+            Self::Fixed => "Fixed",
+            Self::Adaptive => "Adaptive",
+        }
+    }
+}
+
+/// Picks the step as a fraction of the shortest encounter time scale in the system
+pub struct StepController {
+    /// fraction of the encounter time scale a step may cover
+    pub accuracy: f64,
+
+    pub min_step: f64,
+    pub max_step: f64,
+
+    /// how many times the step may grow between two consecutive steps
+    pub max_growth: f64,
+}
+
+impl StepController {
+    pub fn new(accuracy: f64, max_step: f64) -> Self {
+        Self {
+            accuracy,
+            min_step: 1e-3,
+            max_step,
+            max_growth: 2.0,
+        }
+    }
+
+    /// Step for the current state of `objects` given the previous step
+    pub fn step(&self, objects: &[CosmosObject], previous: f64) -> f64 {
+        // longer time scales give the max step anyway
+        let mut time_scale = self.max_step / self.accuracy;
+
+        // no pair closes in faster than twice the top speed or falls faster than twice the top mass
+        let max_speed = objects
+            .iter()
+            .map(|object| object.velocity.length())
+            .fold(0.0, f64::max);
+        let max_mass = objects.iter().map(|object| object.mass).fold(0.0, f64::max);
+
+        // sweep along x, the x distance of a pair bounds its time scale from below
+        let mut sorted = objects.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+
+        for (i, a) in sorted.iter().enumerate() {
+            for b in &sorted[i + 1..] {
+                let dx = b.position.x - a.position.x;
+
+                let crossing = dx / (2.0 * max_speed);
+                let free_fall = (dx.powi(3) / (GRAVITIONAL_CONST * 2.0 * max_mass)).sqrt();
+
+                if crossing.min(free_fall) >= time_scale {
+                    break;
+                }
+
+                time_scale = time_scale.min(encounter_time_scale(a, b));
+            }
+        }
+
+        let mut step = self.accuracy * time_scale;
+
+        if previous > 0.0 {
+            step = step.min(previous * self.max_growth);
+        }
+
+        // a max step below the min step wins, `clamp` would panic
+        step.clamp(self.min_step.min(self.max_step), self.max_step)
+    }
+}
+
+/// The smaller of the distance to relative speed ratio and the free-fall time of a pair,
+/// the latter keeps the step small for close bodies at rest relative to each other
+fn encounter_time_scale(a: &CosmosObject, b: &CosmosObject) -> f64 {
+    let dist = (b.position - a.position).length();
+    let speed = (b.velocity - a.velocity).length();
+
+    let crossing = dist / speed;
+    let free_fall = (dist.powi(3) / (GRAVITIONAL_CONST * (a.mass + b.mass))).sqrt();
+
+    crossing.min(free_fall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::DVec2;
+
+    #[test]
+    fn sweep_matches_the_full_scan() {
+        let sun = CosmosObject {
+            mass: 2e30,
+            ..Default::default()
+        };
+
+        // scattered bodies, the pseudo random layout is the same on every run
+        let objects = (1..200)
+            .map(|i| {
+                let i = i as f64;
+
+                CosmosObject {
+                    mass: 1e20 * i,
+                    position: DVec2::new((i * 12.9898).sin(), (i * 78.233).sin()) * 3000.0,
+                    velocity: DVec2::new((i * 3.1).cos(), (i * 5.7).sin()) * 1e-4,
+                    ..Default::default()
+                }
+            })
+            .chain([sun])
+            .collect::<Vec<_>>();
+
+        let mut time_scale = f64::INFINITY;
+
+        for i in 0..objects.len() {
+            for j in i + 1..objects.len() {
+                time_scale = time_scale.min(encounter_time_scale(&objects[i], &objects[j]));
+            }
+        }
+
+        let controller = StepController::new(0.01, f64::INFINITY);
+
+        assert_eq!(controller.step(&objects, 0.0), 0.01 * time_scale);
+    }
+}
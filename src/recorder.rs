@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::physics::{Frame, KM_PER_VPX};
+
+/// Magic bytes opening a columnar file, the last one is the format version
+const COLUMNAR_MAGIC: &[u8; 8] = b"CELCOL\0\x01";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordFormat {
+    /// a row per body per sample with a header
+    Csv = 0,
+    /// see [`Recorder::write_columnar`]
+    Columnar = 1,
+}
+
+impl RecordFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Columnar];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Columnar => "Columnar",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Columnar => "ccol",
+        }
+    }
+
+    /// CSV for `.csv` files, columnar for the rest
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Columnar,
+        }
+    }
+}
+
+/// Samples the state of every body once per `interval` of simulated time,
+/// counting the intervals from the first sample.
+///
+/// Lengths are stored in km, velocities in km/sec and accelerations in km/sec^2
+pub struct Recorder {
+    /// simulated seconds between two samples
+    pub interval: f64,
+
+    /// elapsed time of the first sample
+    origin: Option<f64>,
+    /// intervals between the first and the last sample
+    last: Option<f64>,
+    samples: usize,
+
+    elapsed: Vec<f64>,
+    /// id of the body, stable across merges and deletions of the other bodies
+    id: Vec<u64>,
+    /// of the named bodies by id
+    names: BTreeMap<u64, Arc<str>>,
+    mass: Vec<f64>,
+    position: [Vec<f64>; 2],
+    velocity: [Vec<f64>; 2],
+    acceleration: [Vec<f64>; 2],
+}
+
+impl Recorder {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            origin: None,
+            last: None,
+            samples: 0,
+            elapsed: Vec::new(),
+            id: Vec::new(),
+            names: BTreeMap::new(),
+            mass: Vec::new(),
+            position: Default::default(),
+            velocity: Default::default(),
+            acceleration: Default::default(),
+        }
+    }
+
+    /// Samples `frame` if it's in another interval than the last sample,
+    /// in either direction of time
+    pub fn record(&mut self, frame: &Frame) -> bool {
+        let origin = *self.origin.get_or_insert(frame.elapsed);
+
+        // leaves room for the rounding of the accumulated elapsed time
+        let intervals = ((frame.elapsed - origin) / self.interval + 1e-9).floor();
+
+        if self.last == Some(intervals) {
+            return false;
+        }
+
+        for object in &frame.objects {
+            let position = object.position * KM_PER_VPX;
+            let velocity = object.velocity * KM_PER_VPX;
+            let acceleration = object.acceleration * KM_PER_VPX;
+
+            self.elapsed.push(frame.elapsed);
+            self.id.push(object.id);
+            self.mass.push(object.mass);
+
+            if let Some(name) = &object.name {
+                self.names.insert(object.id, name.clone());
+            }
+
+            for (column, value) in [
+                (&mut self.position, position),
+                (&mut self.velocity, velocity),
+                (&mut self.acceleration, acceleration),
+            ] {
+                column[0].push(value.x);
+                column[1].push(value.y);
+            }
+        }
+
+        self.last = Some(intervals);
+        self.samples += 1;
+
+        true
+    }
+
+    /// Number of recorded frames
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Number of rows, a row per body per sample
+    pub fn rows(&self) -> usize {
+        self.elapsed.len()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>, format: RecordFormat) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match format {
+            RecordFormat::Csv => self.write_csv(&mut writer)?,
+            RecordFormat::Columnar => self.write_columnar(&mut writer)?,
+        }
+
+        writer.flush()
+    }
+
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "elapsed,id,name,mass,x,y,vx,vy,ax,ay")?;
+
+        for row in 0..self.rows() {
+            let id = self.id[row];
+            let name = self
+                .names
+                .get(&id)
+                .map_or(String::new(), |name| csv_field(name));
+
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                self.elapsed[row],
+                id,
+                name,
+                self.mass[row],
+                self.position[0][row],
+                self.position[1][row],
+                self.velocity[0][row],
+                self.velocity[1][row],
+                self.acceleration[0][row],
+                self.acceleration[1][row],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the columns one after another, all numbers are little endian:
+    ///
+    /// - 8 magic bytes `CELCOL\0\x01`
+    /// - `u64` number of rows and `u32` number of columns
+    /// - for every column its name as a `u8` length and UTF-8 bytes, then a `u8` type:
+    ///   0 for `f64`, 1 for `u64`
+    /// - the values of every column in the same order
+    /// - `u32` number of named bodies, for every one its `u64` id
+    ///   and its name as a `u16` length and UTF-8 bytes
+    pub fn write_columnar(&self, mut writer: impl Write) -> io::Result<()> {
+        let f64_columns = [
+            ("elapsed", &self.elapsed),
+            ("mass", &self.mass),
+            ("x", &self.position[0]),
+            ("y", &self.position[1]),
+            ("vx", &self.velocity[0]),
+            ("vy", &self.velocity[1]),
+            ("ax", &self.acceleration[0]),
+            ("ay", &self.acceleration[1]),
+        ];
+
+        writer.write_all(COLUMNAR_MAGIC)?;
+        writer.write_all(&(self.rows() as u64).to_le_bytes())?;
+        writer.write_all(&(f64_columns.len() as u32 + 1).to_le_bytes())?;
+
+        write_column_header(&mut writer, "id", 1)?;
+
+        for (name, _) in f64_columns {
+            write_column_header(&mut writer, name, 0)?;
+        }
+
+        for &id in &self.id {
+            writer.write_all(&id.to_le_bytes())?;
+        }
+
+        for (_, column) in f64_columns {
+            for value in column {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        writer.write_all(&(self.names.len() as u32).to_le_bytes())?;
+
+        for (id, name) in &self.names {
+            // longer names are cut at a character boundary
+            let mut length = name.len().min(u16::MAX as usize);
+
+            while !name.is_char_boundary(length) {
+                length -= 1;
+            }
+
+            writer.write_all(&id.to_le_bytes())?;
+            writer.write_all(&(length as u16).to_le_bytes())?;
+            writer.write_all(&name.as_bytes()[..length])?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_column_header(writer: &mut impl Write, name: &str, kind: u8) -> io::Result<()> {
+    writer.write_all(&[name.len() as u8])?;
+    writer.write_all(name.as_bytes())?;
+    writer.write_all(&[kind])
+}
+
+/// `text` quoted if it holds a separator, a quote or a line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosmos_object::CosmosObject;
+    use crate::math::DVec2;
+
+    const COMET: &str = "Halley, \"the comet\"";
+
+    /// Two bodies sampled at 0 and 10 seconds, the frame at 5 seconds is skipped
+    fn recorded() -> Recorder {
+        let objects = vec![
+            CosmosObject {
+                id: 1,
+                name: Some("Sun".into()),
+                mass: 2.0,
+                position: DVec2::new(1.0, 2.0),
+                velocity: DVec2::new(0.5, 0.0),
+                ..Default::default()
+            },
+            CosmosObject {
+                id: 7,
+                name: Some(COMET.into()),
+                mass: 1.0,
+                ..Default::default()
+            },
+        ];
+
+        let mut recorder = Recorder::new(10.0);
+
+        for elapsed in [0.0, 5.0, 10.0] {
+            recorder.record(&Frame {
+                objects: objects.clone(),
+                elapsed,
+                ..Default::default()
+            });
+        }
+
+        recorder
+    }
+
+    #[test]
+    fn csv_has_a_row_per_body_per_sample() {
+        let mut csv = Vec::new();
+        recorded().write_csv(&mut csv).unwrap();
+
+        let expected = [
+            "elapsed,id,name,mass,x,y,vx,vy,ax,ay",
+            "0,1,Sun,2,100000,200000,50000,0,0,0",
+            "0,7,\"Halley, \"\"the comet\"\"\",1,0,0,0,0,0,0",
+            "10,1,Sun,2,100000,200000,50000,0,0,0",
+            "10,7,\"Halley, \"\"the comet\"\"\",1,0,0,0,0,0,0",
+        ];
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap().lines().collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn columnar_has_the_columns_then_the_names() {
+        let mut bytes = Vec::new();
+        recorded().write_columnar(&mut bytes).unwrap();
+
+        let mut rest = bytes.as_slice();
+        let mut take = |count: usize| {
+            let (taken, others) = rest.split_at(count);
+            rest = others;
+            taken.to_vec()
+        };
+
+        assert_eq!(take(8), COLUMNAR_MAGIC);
+        assert_eq!(take(8), 4u64.to_le_bytes());
+        assert_eq!(take(4), 9u32.to_le_bytes());
+
+        let names = ["id", "elapsed", "mass", "x", "y", "vx", "vy", "ax", "ay"];
+
+        for name in names {
+            let length = take(1)[0] as usize;
+            let kind = if name == "id" { 1 } else { 0 };
+
+            assert_eq!(take(length), name.as_bytes());
+            assert_eq!(take(1), [kind]);
+        }
+
+        for id in [1u64, 7, 1, 7] {
+            assert_eq!(take(8), id.to_le_bytes());
+        }
+
+        for elapsed in [0.0f64, 0.0, 10.0, 10.0] {
+            assert_eq!(take(8), elapsed.to_le_bytes());
+        }
+
+        // mass, x, y, vx, vy, ax and ay
+        take(7 * 4 * 8);
+
+        assert_eq!(take(4), 2u32.to_le_bytes());
+
+        for (id, name) in [(1u64, "Sun"), (7, COMET)] {
+            assert_eq!(take(8), id.to_le_bytes());
+            assert_eq!(take(2), (name.len() as u16).to_le_bytes());
+            assert_eq!(take(name.len()), name.as_bytes());
+        }
+
+        assert!(rest.is_empty());
+    }
+}
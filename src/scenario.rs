@@ -0,0 +1,270 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::{fmt, fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cosmos_object::{CosmosObject, DEFAULT_COLOR};
+use crate::math::DVec2;
+use crate::orbit::{Anomaly, KeplerOrbit};
+use crate::physics::KM_PER_VPX;
+
+/// The Sun and the Earth
+pub const DEFAULT_SCENARIO: &str = include_str!("../assets/scenarios/sun_earth.ron");
+
+/// System to simulate, stored as RON.
+///
+/// Lengths are in km, velocities in km/sec, masses in kg and angles in degrees
+#[derive(Serialize, Deserialize)]
+pub struct Scenario {
+    /// simulated seconds in one real second
+    #[serde(default = "default_time_speed")]
+    pub time_speed: f64,
+
+    #[serde(default)]
+    pub camera: Camera,
+
+    pub bodies: Vec<Body>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Camera {
+    /// point in the middle of the screen
+    pub center: (f64, f64),
+
+    /// screen points per vpx
+    pub zoom: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Body {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<(u8, u8, u8)>,
+
+    pub mass: f64,
+
+    pub radius: f64,
+
+    /// relative to the parent if `orbit` is given
+    #[serde(default)]
+    pub position: (f64, f64),
+
+    /// relative to the parent if `orbit` is given
+    #[serde(default)]
+    pub velocity: (f64, f64),
+
+    /// places the body on an orbit around the parent, `position` and `velocity` are added on top
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orbit: Option<Orbit>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Orbit {
+    /// name of a body listed earlier
+    pub parent: String,
+
+    /// radius of circular orbits, negative for hyperbolic ones
+    #[serde(alias = "distance")]
+    pub semi_major_axis: f64,
+
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub eccentricity: f64,
+
+    /// angle of the periapsis from the x axis
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub argument_of_periapsis: f64,
+
+    /// true anomaly, angle of the body from the periapsis in the direction of the motion
+    #[serde(default)]
+    pub anomaly: f64,
+
+    /// replaces `anomaly` if given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_anomaly: Option<f64>,
+
+    /// 1 to go counter-clockwise on the screen, -1 to go clockwise
+    #[serde(default = "default_direction")]
+    pub direction: f64,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnknownParent(String),
+    /// orbit around the named parent without a finite state, with the reason
+    InvalidOrbit(String, &'static str),
+}
+
+impl Scenario {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, ScenarioError> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        Ok(fs::write(path, self.to_ron()?)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ScenarioError> {
+        let config = ron::ser::PrettyConfig::default().struct_names(true);
+
+        Ok(ron::ser::to_string_pretty(self, config)?)
+    }
+
+    /// Describes `objects` by their absolute positions and velocities
+    pub fn from_objects(objects: &[CosmosObject], time_speed: f64, camera: Camera) -> Self {
+        let bodies = objects
+            .iter()
+            .map(|object| Body {
+                name: object.name.as_deref().map(str::to_owned),
+                color: (object.color != DEFAULT_COLOR).then_some(object.color.into()),
+                mass: object.mass,
+                radius: object.radius * KM_PER_VPX,
+                position: to_km(object.position),
+                velocity: to_km(object.velocity),
+                orbit: None,
+            })
+            .collect();
+
+        Self {
+            time_speed,
+            camera,
+            bodies,
+        }
+    }
+
+    /// Builds the objects, resolving orbits against the bodies listed before them
+    pub fn objects(&self) -> Result<Vec<CosmosObject>, ScenarioError> {
+        let mut objects = Vec::<CosmosObject>::with_capacity(self.bodies.len());
+
+        for body in &self.bodies {
+            let mut object = CosmosObject {
+                name: body.name.as_deref().map(Arc::from),
+                color: body.color.map_or(DEFAULT_COLOR, Into::into),
+                mass: body.mass,
+                radius: body.radius / KM_PER_VPX,
+                ..Default::default()
+            };
+
+            if let Some(orbit) = &body.orbit {
+                let parent = objects
+                    .iter()
+                    .find(|object| object.name.as_deref() == Some(orbit.parent.as_str()))
+                    .ok_or_else(|| ScenarioError::UnknownParent(orbit.parent.clone()))?;
+
+                let anomaly = match orbit.mean_anomaly {
+                    Some(mean) => Anomaly::Mean(mean.to_radians()),
+                    None => Anomaly::True(orbit.anomaly.to_radians()),
+                };
+
+                let kepler_orbit = KeplerOrbit {
+                    semi_major_axis: orbit.semi_major_axis / KM_PER_VPX,
+                    eccentricity: orbit.eccentricity,
+                    argument_of_periapsis: orbit.argument_of_periapsis.to_radians(),
+                    anomaly,
+                    direction: orbit.direction,
+                };
+
+                if let Some(reason) = kepler_orbit.invalid_reason() {
+                    return Err(ScenarioError::InvalidOrbit(orbit.parent.clone(), reason));
+                }
+
+                object = object.on_orbit(parent, &kepler_orbit);
+            }
+
+            object.position += from_km(body.position);
+            object.velocity += from_km(body.velocity);
+
+            objects.push(object);
+        }
+
+        Ok(objects)
+    }
+}
+
+impl Camera {
+    /// Camera center in vpx
+    pub fn center(&self) -> DVec2 {
+        from_km(self.center)
+    }
+
+    pub fn new(center: DVec2, zoom: f32) -> Self {
+        Self {
+            center: to_km(center),
+            zoom,
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            center: (0.0, 0.0),
+            zoom: 1.0,
+        }
+    }
+}
+
+fn from_km((x, y): (f64, f64)) -> DVec2 {
+    DVec2::new(x, y) / KM_PER_VPX
+}
+
+fn to_km(vec: DVec2) -> (f64, f64) {
+    let vec = vec * KM_PER_VPX;
+
+    (vec.x, vec.y)
+}
+
+fn default_time_speed() -> f64 {
+    60. * 60. * 24.
+}
+
+fn default_direction() -> f64 {
+    1.0
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::Serialize(error) => write!(f, "{error}"),
+            Self::UnknownParent(name) => write!(f, "unknown parent body `{name}`"),
+            Self::InvalidOrbit(parent, reason) => {
+                write!(f, "invalid orbit around `{parent}`: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ScenarioError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<ron::Error> for ScenarioError {
+    fn from(error: ron::Error) -> Self {
+        Self::Serialize(error)
+    }
+}
@@ -1,5 +0,0 @@
-mod painter;
-mod time;
-
-pub use painter::Painter;
-pub use time::{format_time, format_time_ord};